@@ -12,6 +12,7 @@ use std::{
     fs::File,
     io::{self, Cursor},
     net::IpAddr,
+    thread,
 };
 use tokio::sync::mpsc::{self, channel};
 
@@ -64,6 +65,10 @@ fn store(ip: IpAddr) -> io::Result<String> {
     Ok(r)
 }
 
+/// The native stack programs run on. The workers' own stacks are too small for the nesting
+/// programs are allowed.
+const PROGRAM_STACK_SIZE: usize = 16 << 20;
+
 #[derive(serde::Deserialize, Debug, Clone)]
 struct Program {
     s: String,
@@ -84,7 +89,16 @@ async fn run(
 ) -> impl Responder {
     s.input.retain(|c| c != '\r');
 
-    let result = std::panic::catch_unwind(|| run_with_input(&s.s, &mut Cursor::new(&s.input)));
+    let (code, input) = (s.s.clone(), s.input.clone());
+    let result = thread::Builder::new()
+        .stack_size(PROGRAM_STACK_SIZE)
+        .spawn(move || {
+            run_with_input(&code, &mut Cursor::new(&input))
+                .map(|v| v.iter().format(",").to_string())
+                .map_err(|e| e.to_string())
+        })
+        .expect("failed to start the program's thread")
+        .join();
 
     if let Some(ip) = req.peer_addr().map(|x| x.ip()) {
         if let Err(e) = tx
@@ -92,8 +106,8 @@ async fn run(
                 ip,
                 program: s.0.clone(),
                 result: match &result {
-                    Ok(Ok(v)) => format!("Ok: [{}]", v.iter().format(",")),
-                    Ok(Err(e)) => format!("\x1b[31mErr:\x1b[0m {:?}", e),
+                    Ok(Ok(v)) => format!("Ok: [{}]", v),
+                    Ok(Err(e)) => format!("\x1b[31mErr:\x1b[0m {}", e),
                     Err(e) => format!("\x1b[1;31mpanicked at\x1b[0m '{:?}'", e),
                 },
             })
@@ -104,8 +118,8 @@ async fn run(
     }
 
     match result {
-        Ok(Ok(v)) => HttpResponse::Ok().body(iframe!("[{}]", v.into_iter().format(","))),
-        Ok(Err(e)) => HttpResponse::BadRequest().body(iframe!("{:?}", e)),
+        Ok(Ok(v)) => HttpResponse::Ok().body(iframe!("[{}]", v)),
+        Ok(Err(e)) => HttpResponse::BadRequest().body(iframe!("{}", e)),
         Err(e) => HttpResponse::InternalServerError().body(iframe!("panicked at '{:?}'", e)),
     }
 }
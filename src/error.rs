@@ -1,5 +1,8 @@
-use crate::stack::Value;
-use std::io;
+use crate::{
+    stack::{Map, Value},
+    util::str_ext::Span,
+};
+use std::{error, fmt, io};
 
 pub mod runtime {
     pub use super::RuntimeError;
@@ -8,7 +11,6 @@ pub mod runtime {
 
 pub mod syntax {
     pub use super::SyntaxError;
-    pub use super::SyntaxResult;
 }
 
 pub mod both {
@@ -16,10 +18,60 @@ pub mod both {
     pub use super::syntax::*;
 }
 
+/// An error, along with the span of the operator that caused it, if known.
 #[derive(Debug)]
 pub enum Error {
-    Syntax(SyntaxError),
-    Runtime(RuntimeError),
+    Syntax(SyntaxError, Option<Span>),
+    Runtime(RuntimeError, Option<Span>),
+}
+
+impl Error {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Syntax(_, span) | Self::Runtime(_, span) => *span,
+        }
+    }
+
+    /// Attaches `span` to the error, unless it already came from a more specific location.
+    pub(crate) fn at(mut self, at: Span) -> Self {
+        match &mut self {
+            Self::Syntax(_, span) | Self::Runtime(_, span) => {
+                span.get_or_insert(at);
+            }
+        }
+        self
+    }
+
+    /// Forgets where the error happened, for errors in code that `eval` parsed, whose spans are
+    /// positions in that code rather than in the program.
+    pub(crate) fn unlocated(mut self) -> Self {
+        match &mut self {
+            Self::Syntax(_, span) | Self::Runtime(_, span) => *span = None,
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, e, span): (_, &dyn fmt::Display, _) = match self {
+            Self::Syntax(e, span) => ("syntax", e, span),
+            Self::Runtime(e, span) => ("runtime", e, span),
+        };
+        match span {
+            Some(span) => write!(f, "{} error at {}: {}", kind, span, e),
+            None => write!(f, "{} error: {}", kind, e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Runtime(e, _) => Some(e),
+            Self::Syntax(e, _) => Some(e),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -30,13 +82,131 @@ pub enum RuntimeError {
     InvalidCast(Value, &'static str),
     OutOfBounds(usize, i64),
     FoldingEmptyArray,
-    InvalidValueString(String),
     IterationMax(usize),
+    UndefinedWord(String),
+    UndefinedVariable(String),
+    RecursionMax(usize),
+    /// A value thrown with `throw`.
+    Thrown(Value),
+    /// A math function applied outside of its domain, like the square root of a negative number.
+    Domain(Vec<Value>, &'static str),
+    /// A syntax error in code parsed while running, by `eval` or `unquote`.
+    Syntax(SyntaxError, Option<Span>),
+}
+
+impl RuntimeError {
+    /// A short name for the kind of error, as `try` reports it.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::StackEmpty => "stack_empty",
+            Self::Io(_) => "io",
+            Self::InvalidOperation(..) => "invalid_operation",
+            Self::InvalidCast(..) => "invalid_cast",
+            Self::OutOfBounds(..) => "out_of_bounds",
+            Self::FoldingEmptyArray => "folding_empty_array",
+            Self::IterationMax(_) => "iteration_max",
+            Self::UndefinedWord(_) => "undefined_word",
+            Self::UndefinedVariable(_) => "undefined_variable",
+            Self::RecursionMax(_) => "recursion_max",
+            Self::Thrown(_) => "thrown",
+            Self::Syntax(..) => "syntax",
+            Self::Domain(..) => "domain",
+        }
+    }
+
+    /// The value `try` hands to its handler: a map with the `"kind"` of error and its
+    /// `"message"`. A thrown map is handed over as it is, so a handler can throw it again.
+    pub fn into_value(self) -> Value {
+        if let Self::Thrown(Value::Map(m)) = self {
+            return Value::Map(m);
+        }
+        let message = match &self {
+            Self::Thrown(Value::Str(s)) => s.to_string(),
+            e => e.to_string(),
+        };
+        Value::Map(Map::from_iter([
+            (Value::from("kind"), Value::from(self.kind())),
+            (Value::from("message"), Value::from(message)),
+        ]))
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StackEmpty => f.write_str("the stack is empty"),
+            Self::Io(_) => write!(f, "failed to read input"),
+            Self::InvalidOperation(operands, op) => {
+                write!(f, "cannot apply `{}` to ", op)?;
+                write_operands(f, operands)
+            }
+            Self::Domain(operands, op) => {
+                write!(f, "`{}` is not defined for ", op)?;
+                write_operands(f, operands)
+            }
+            Self::InvalidCast(v, t) => {
+                write!(
+                    f,
+                    "cannot convert {} {} to {}",
+                    v.type_name(),
+                    v.literal(),
+                    t
+                )
+            }
+            Self::OutOfBounds(len, i) => write!(
+                f,
+                "index {} is out of bounds for a stack of length {}",
+                i, len
+            ),
+            Self::FoldingEmptyArray => f.write_str("cannot fold an empty array"),
+            Self::IterationMax(n) => write!(f, "loop exceeded the maximum of {} iterations", n),
+            Self::UndefinedWord(w) => write!(f, "`{}` is called before it is defined", w),
+            Self::UndefinedVariable(v) => write!(f, "`{}` is read before it is assigned", v),
+            Self::RecursionMax(n) => {
+                write!(
+                    f,
+                    "blocks and words nested deeper than the maximum of {}",
+                    n
+                )
+            }
+            Self::Thrown(v) => match v {
+                Value::Str(s) => f.write_str(s),
+                Value::Map(m) => match m.get(&Value::from("message")) {
+                    Some(Value::Str(s)) => f.write_str(s),
+                    _ => write!(f, "uncaught {} {}", v.type_name(), v.literal()),
+                },
+                v => write!(f, "uncaught {} {}", v.type_name(), v.literal()),
+            },
+            Self::Syntax(e, Some(span)) => write!(f, "syntax error in code at {}: {}", span, e),
+            Self::Syntax(e, None) => write!(f, "syntax error in code: {}", e),
+        }
+    }
+}
+
+fn write_operands(f: &mut fmt::Formatter<'_>, operands: &[Value]) -> fmt::Result {
+    for (i, v) in operands.iter().enumerate() {
+        match i {
+            0 => (),
+            i if i == operands.len() - 1 => f.write_str(" and ")?,
+            _ => f.write_str(", ")?,
+        }
+        write!(f, "{} {}", v.type_name(), v.literal())?;
+    }
+    Ok(())
+}
+
+impl error::Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<RuntimeError> for Error {
     fn from(e: RuntimeError) -> Self {
-        Self::Runtime(e)
+        Self::Runtime(e, None)
     }
 }
 
@@ -77,28 +247,70 @@ macro_rules! rt_error {
             .into(),
         )
     };
+    (op: $a:expr, $b:expr, $c:expr => [$op:ident]) => {
+        return ::std::result::Result::Err(
+            $crate::error::RuntimeError::InvalidOperation(
+                ::std::vec![
+                    $crate::Value::from($a),
+                    $crate::Value::from($b),
+                    $crate::Value::from($c),
+                ],
+                ::std::stringify!($op),
+            )
+            .into(),
+        )
+    };
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct SyntaxError(String);
+pub enum SyntaxError {
+    UnknownToken(String),
+    InvalidEscape(String),
+    InvalidChar(String),
+    InvalidNumber(String),
+    Unclosed(&'static str),
+    Unmatched(&'static str),
+    Mismatched { expected: char, found: char },
+    Builtin(String),
+    InvalidWordName(String),
+    ExpectedBody(String),
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownToken(t) => write!(f, "unknown token `{}`", t),
+            Self::InvalidEscape(e) => write!(f, "invalid escape sequence `{}`", e),
+            Self::InvalidChar(c) => write!(f, "invalid char literal `{}`", c),
+            Self::InvalidNumber(n) => write!(f, "invalid number literal `{}`", n),
+            Self::Unclosed(c) => write!(f, "unclosed `{}`", c),
+            Self::Unmatched(c) => write!(f, "unmatched `{}`", c),
+            Self::Mismatched { expected, found } => {
+                write!(f, "expected `{}` but found `{}`", expected, found)
+            }
+            Self::Builtin(w) => write!(f, "cannot redefine built-in `{}`", w),
+            Self::InvalidWordName(w) => write!(f, "`{}` is not a valid word name", w),
+            Self::ExpectedBody(w) => write!(f, "expected a block with the body of `{}`", w),
+        }
+    }
+}
+
+impl error::Error for SyntaxError {}
 
 impl From<&str> for SyntaxError {
     fn from(s: &str) -> Self {
-        Self(s.into())
+        Self::UnknownToken(s.into())
     }
 }
 
 impl From<String> for SyntaxError {
     fn from(s: String) -> Self {
-        Self(s)
+        Self::UnknownToken(s)
     }
 }
 
 impl From<SyntaxError> for Error {
     fn from(e: SyntaxError) -> Self {
-        Self::Syntax(e)
+        Self::Syntax(e, None)
     }
 }
-
-#[allow(dead_code)]
-pub type SyntaxResult<T> = Result<T, SyntaxError>;
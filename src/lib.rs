@@ -2,27 +2,55 @@
 
 mod error;
 mod ops;
+mod program;
 mod stack;
 mod util;
 
 pub use error::Error;
-use stack::Stack;
-pub use stack::Value;
+pub use program::Program;
+pub use stack::{Map, Shared, Stack, Value};
 use std::io::{self, BufRead, BufReader};
-use util::str_ext::StrExt;
+pub use util::{
+    overflow::Overflow,
+    str_ext::{Mode, Span},
+    text::Segmentation,
+};
 
 pub struct Repl<'i> {
     stack: stack::Stack<'i>,
+    mode: Mode,
 }
 
 impl<'i> Repl<'i> {
     pub fn new<I: BufRead>(i: &'i mut I) -> Self {
-        Self { stack: Stack::with_input(i) }
+        Self::with_mode(i, Mode::default())
+    }
+
+    pub fn with_mode<I: BufRead>(i: &'i mut I, mode: Mode) -> Self {
+        Self {
+            stack: Stack::with_input(i),
+            mode,
+        }
+    }
+
+    pub fn set_segmentation(&mut self, segmentation: Segmentation) {
+        self.stack.set_segmentation(segmentation);
+    }
+
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.stack.set_overflow(overflow);
+    }
+
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.stack.set_max_call_depth(depth);
     }
 
     pub fn next_line(&mut self, s: &str) {
-        if let Err(e) = ops::parse_and_execute(s.split_tokens(), &mut self.stack) {
-            eprintln!("{:?}", e);
+        let known = self.stack.names();
+        if let Err(e) =
+            Program::parse_for(s, self.mode, &known).and_then(|p| p.run(&mut self.stack))
+        {
+            eprintln!("{}", e);
         }
     }
 
@@ -32,16 +60,27 @@ impl<'i> Repl<'i> {
 }
 
 pub fn run(s: &str) -> Result<Vec<Value>, error::Error> {
-    run_with_input(s, &mut BufReader::new(io::stdin()))
+    run_with_mode(s, Mode::default())
+}
+
+pub fn run_with_mode(s: &str, mode: Mode) -> Result<Vec<Value>, error::Error> {
+    run_with_input_and_mode(s, &mut BufReader::new(io::stdin()), mode)
 }
 
 pub fn run_on(s: &str, mut stack: Stack<'_>) -> Result<Vec<Value>, error::Error> {
-    ops::parse_and_execute(s.split_tokens(), &mut stack)?;
+    let known = stack.names();
+    Program::parse_for(s, Mode::default(), &known)?.run(&mut stack)?;
     Ok(stack.into_vec())
 }
 
 pub fn run_with_input(s: &str, i: &mut dyn BufRead) -> Result<Vec<Value>, error::Error> {
-    let mut stack = stack::Stack::with_input(i);
-    ops::parse_and_execute(s.split_tokens(), &mut stack)?;
-    Ok(stack.into_vec())
+    run_with_input_and_mode(s, i, Mode::default())
+}
+
+pub fn run_with_input_and_mode(
+    s: &str,
+    i: &mut dyn BufRead,
+    mode: Mode,
+) -> Result<Vec<Value>, error::Error> {
+    Program::parse_with_mode(s, mode)?.run_with_input(i)
 }
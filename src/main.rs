@@ -1,6 +1,6 @@
 #![deny(unused_must_use)]
 
-use stack_machine::{run, Repl};
+use stack_machine::{Mode, Overflow, Program, Repl, Segmentation, Stack};
 
 use std::{
     env::args,
@@ -9,14 +9,42 @@ use std::{
 };
 
 fn main() -> io::Result<()> {
-    if let Some(file) = args().nth(1) {
+    let mut args = args().skip(1).peekable();
+    let mut mode = Mode::Spaced;
+    let mut segmentation = Segmentation::CodePoints;
+    let mut overflow = Overflow::Promote;
+    while let Some(flag) = args.next_if(|a| a.starts_with("--")) {
+        match flag.as_str() {
+            "--golf" => mode = Mode::Golf,
+            "--graphemes" => segmentation = Segmentation::Graphemes,
+            "--overflow=promote" => overflow = Overflow::Promote,
+            "--overflow=error" => overflow = Overflow::Error,
+            "--overflow=wrapping" => overflow = Overflow::Wrapping,
+            "--overflow=saturating" => overflow = Overflow::Saturating,
+            "--overflow=float" => overflow = Overflow::Float,
+            _ => {
+                eprintln!("unknown flag {}", flag);
+                return Ok(());
+            }
+        }
+    }
+    if let Some(file) = args.next() {
         let f = fs::read_to_string(file)?;
-        println!("{:?}", run(&f));
+        let mut input = BufReader::new(io::stdin());
+        let mut stack = Stack::with_input(&mut input);
+        stack.set_segmentation(segmentation);
+        stack.set_overflow(overflow);
+        match Program::parse_with_mode(&f, mode).and_then(|p| p.run(&mut stack)) {
+            Ok(()) => println!("{:?}", stack.into_vec()),
+            Err(e) => eprintln!("{}", e),
+        }
     } else {
         let mut s = String::new();
         let stdin = io::stdin();
         let mut input = BufReader::new(io::stdin());
-        let mut repl = Repl::new(&mut input);
+        let mut repl = Repl::with_mode(&mut input, mode);
+        repl.set_segmentation(segmentation);
+        repl.set_overflow(overflow);
         while {
             s.clear();
             stdin.read_line(&mut s)? > 0
@@ -1,8 +1,9 @@
 use super::Operator;
 use crate::{
     error::runtime::*,
-    ops::calculate,
-    stack::{value::Value, Stack},
+    ops::{calculate, run_for_element},
+    stack::{value::Value, Map, Stack},
+    util::{sets, text::unit_value},
 };
 use std::{
     cmp::Ordering,
@@ -24,8 +25,15 @@ impl FromStr for BinaryOp {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, ()> {
         let op: fn(Value, Value, &mut Stack) -> Result<Value, crate::Error> = match s {
-            "+" => |a, b, _| Value::add(a, b).map_err(crate::Error::from),
-            "-" => |a, b, _| Value::sub(a, b).map_err(crate::Error::from),
+            "+" => |a, b, s| Value::add_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "-" => |a, b, s| match (a, b) {
+                (Value::Str(a), Value::Str(b)) => Ok(Value::from(s.segmentation().set_op(
+                    &a,
+                    &b,
+                    sets::difference,
+                ))),
+                (a, b) => Value::sub_with(a, b, s.overflow()).map_err(crate::Error::from),
+            },
             "*" => |a, b, s| match (a, b) {
                 (Value::Array(a), Value::Block(b)) => {
                     let mut a = a.into_iter();
@@ -36,12 +44,32 @@ impl FromStr for BinaryOp {
                         calculate(v, &b, &mut temp_stack)
                     })
                 }
-                (a, b) => Value::mul(a, b).map_err(crate::Error::from),
+                (a, b) => Value::mul_with(a, b, s.overflow()).map_err(crate::Error::from),
+            },
+            "/" => |a, b, s| Value::div_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "e/" => |a, b, s| Value::exact_div_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "&" => |a, b, s| match (a, b) {
+                (Value::Str(a), Value::Str(b)) => Ok(Value::from(s.segmentation().set_op(
+                    &a,
+                    &b,
+                    sets::intersection,
+                ))),
+                (a, b) => Value::bitand(a, b).map_err(crate::Error::from),
+            },
+            "|" => |a, b, s| match (a, b) {
+                (Value::Str(a), Value::Str(b)) => {
+                    Ok(Value::from(s.segmentation().set_op(&a, &b, sets::union)))
+                }
+                (a, b) => Value::bitor(a, b).map_err(crate::Error::from),
+            },
+            "^" => |a, b, s| match (a, b) {
+                (Value::Str(a), Value::Str(b)) => Ok(Value::from(s.segmentation().set_op(
+                    &a,
+                    &b,
+                    sets::symmetric_difference,
+                ))),
+                (a, b) => Value::bitxor(a, b).map_err(crate::Error::from),
             },
-            "/" => |a, b, _| Value::div(a, b).map_err(crate::Error::from),
-            "&" => |a, b, _| Value::bitand(a, b).map_err(crate::Error::from),
-            "|" => |a, b, _| Value::bitor(a, b).map_err(crate::Error::from),
-            "^" => |a, b, _| Value::bitxor(a, b).map_err(crate::Error::from),
             "%" => |a, b, s| match (a, b) {
                 (Value::Array(mut a), Value::Block(b)) => {
                     for v in &mut a {
@@ -49,48 +77,61 @@ impl FromStr for BinaryOp {
                     }
                     Ok(Value::Array(a))
                 }
-                (Value::Str(string), Value::Block(b)) => Ok(Value::Str(
-                    string
-                        .chars()
-                        .map(Value::Char)
-                        .map(|c| -> Result<char, crate::Error> {
-                            let mut temp_stack = s.sub_stack();
-                            match calculate(c, &b, &mut temp_stack)? {
-                                Value::Char(c) if temp_stack.is_empty() => Ok(c),
-                                x => crate::rt_error!(convert: x, char),
-                            }
-                        })
-                        .collect::<Result<_, _>>()?,
-                )),
-                (a, b) => Value::rem(a, b).map_err(crate::Error::from),
+                (Value::Str(string), Value::Block(b)) => {
+                    let segmentation = s.segmentation();
+                    let mut mapped = String::with_capacity(string.len());
+                    for u in segmentation.split(&string) {
+                        let mut temp_stack = s.sub_stack();
+                        match calculate(unit_value(u), &b, &mut temp_stack)? {
+                            Value::Char(c) if temp_stack.is_empty() => mapped.push(c),
+                            Value::Str(u) if temp_stack.is_empty() => mapped.push_str(&u),
+                            x => crate::rt_error!(convert: x, char),
+                        }
+                    }
+                    Ok(Value::from(mapped))
+                }
+                (Value::Map(m), Value::Block(b)) => {
+                    let mut mapped = Map::new();
+                    for (k, v) in m {
+                        let mut temp_stack = s.sub_stack();
+                        temp_stack.push(k);
+                        temp_stack.push(v);
+                        run_for_element(&b, &mut temp_stack)?;
+                        match <[Value; 2]>::try_from(temp_stack.take()) {
+                            Ok([k, v]) => mapped.insert(k, v),
+                            Err(entry) => crate::rt_error!(op: entry => [map_entry]),
+                        };
+                    }
+                    Ok(Value::Map(mapped))
+                }
+                (a, b) => Value::rem_with(a, b, s.overflow()).map_err(crate::Error::from),
             },
-            "e&" => |a: Value, b, _| Ok(a.and(b)),
-            "e|" => |a: Value, b, _| Ok(a.or(b)),
             "e<" => |a, b, _| Value::min(a, b).map_err(crate::Error::from),
             "e>" => |a, b, _| Value::max(a, b).map_err(crate::Error::from),
-            ">" => |a, b, _| match (a, b) {
+            ">" => |a, b, stack| match (a, b) {
                 (Value::Array(mut arr), Value::Integer(i)) => {
                     if i > arr.len() as i64 || i < 0 {
                         crate::rt_error!(op: arr, i => [slice_begining])
                     } else {
-                        drop(arr.drain(..(arr.len() - i as usize)));
+                        let len = arr.len();
+                        drop(arr.drain(..(len - i as usize)));
                         Ok(Value::Array(arr))
                     }
                 }
-                (Value::Str(mut s), Value::Integer(i)) => match i {
-                    0 => Ok(Value::Str(String::new())),
-                    i => {
-                        if let Some((i, _)) = s.char_indices().nth_back((i - 1) as usize) {
-                            drop(s.drain(..i));
-                            Ok(Value::Str(s))
-                        } else {
-                            crate::rt_error!(op: s, i => [str_begining])
-                        }
+                (Value::Str(mut s), Value::Integer(i)) => {
+                    let at = usize::try_from(i)
+                        .ok()
+                        .and_then(|i| stack.segmentation().offset_back(&s, i));
+                    if let Some(at) = at {
+                        drop(s.drain(..at));
+                        Ok(Value::Str(s))
+                    } else {
+                        crate::rt_error!(op: s, i => [str_begining])
                     }
-                },
+                }
                 (a, b) => Ok((a.partial_cmp(&b) == Some(Ordering::Greater)).into()),
             },
-            "<" => |a, b, _| match (a, b) {
+            "<" => |a, b, stack| match (a, b) {
                 (Value::Array(mut arr), Value::Integer(i)) => {
                     if i > arr.len() as i64 || i < 0 {
                         crate::rt_error!(op: arr, i => [slice_end])
@@ -99,20 +140,20 @@ impl FromStr for BinaryOp {
                         Ok(Value::Array(arr))
                     }
                 }
-                (Value::Str(mut s), Value::Integer(i)) => match i {
-                    0 => Ok(Value::Str(String::new())),
-                    i => {
-                        if let Some((i, _)) = s.char_indices().nth(i as usize) {
-                            drop(s.drain(i..));
-                            Ok(Value::Str(s))
-                        } else {
-                            crate::rt_error!(op: s, i => [str_end])
-                        }
+                (Value::Str(mut s), Value::Integer(i)) => {
+                    let at = usize::try_from(i)
+                        .ok()
+                        .and_then(|i| stack.segmentation().offset(&s, i));
+                    if let Some(at) = at {
+                        s.truncate(at);
+                        Ok(Value::Str(s))
+                    } else {
+                        crate::rt_error!(op: s, i => [str_end])
                     }
-                },
+                }
                 (a, b) => Ok((a.partial_cmp(&b) == Some(Ordering::Less)).into()),
             },
-            "=" => |a, b, _| match (&a, &b) {
+            "=" => |a, b, stack| match (&a, &b) {
                 (Value::Array(arr), Value::Integer(i)) => {
                     match <i64 as TryInto<usize>>::try_into(*i).map(|i| arr.get(i)) {
                         Ok(Some(v)) => Ok(v.clone()),
@@ -120,20 +161,138 @@ impl FromStr for BinaryOp {
                     }
                 }
                 (Value::Str(s), Value::Integer(i)) => {
-                    match <i64 as TryInto<usize>>::try_into(*i).map(|i| s.chars().nth(i)) {
-                        Ok(Some(v)) => Ok(v.into()),
+                    let segmentation = stack.segmentation();
+                    match <i64 as TryInto<usize>>::try_into(*i).map(|i| segmentation.nth(s, i)) {
+                        Ok(Some(v)) => Ok(unit_value(v)),
                         _ => crate::rt_error!(op: a, b => [index]),
                     }
                 }
                 (a, b) => Ok((a.partial_cmp(b) == Some(Ordering::Equal)).into()),
             },
-            "#" => |a: Value, b, _| a.pow(b).map_err(crate::Error::from),
+            "#" => |a, b, s| match (a, b) {
+                (Value::Str(haystack), needle @ (Value::Str(_) | Value::Char(_))) => Ok(s
+                    .segmentation()
+                    .find(&haystack, &needle.into_string()?)
+                    .map_or(-1, |i| i as i64)
+                    .into()),
+                (a, b) => Value::pow_with(a, b, s.overflow()).map_err(crate::Error::from),
+            },
+            "m=" => |a, k, _| match a {
+                Value::Map(m) => match m.get(&k) {
+                    Some(v) => Ok(v.clone()),
+                    None => crate::rt_error!(op: m, k => [get]),
+                },
+                a => crate::rt_error!(op: a, k => [get]),
+            },
+            "m-" => |a, k, _| match a {
+                Value::Map(mut m) => {
+                    m.remove(&k);
+                    Ok(Value::Map(m))
+                }
+                a => crate::rt_error!(op: a, k => [remove]),
+            },
+            "m?" => |a, k, _| match a {
+                Value::Map(m) => Ok(m.contains_key(&k).into()),
+                a => crate::rt_error!(op: a, k => [has_key]),
+            },
+            "scan" => |a, b, s| match (a, b) {
+                (Value::Array(a), Value::Block(b)) => {
+                    let mut scanned: Vec<Value> = Vec::with_capacity(a.len());
+                    for v in a {
+                        let v = match scanned.last() {
+                            Some(acc) => {
+                                let mut temp_stack = s.sub_stack();
+                                temp_stack.push(acc.clone());
+                                calculate(v, &b, &mut temp_stack)?
+                            }
+                            None => v,
+                        };
+                        scanned.push(v);
+                    }
+                    Ok(scanned.into())
+                }
+                (a, b) => crate::rt_error!(op: a, b => [scan]),
+            },
+            "zip" => |a, b, _| match (a, b) {
+                (Value::Array(a), Value::Array(b)) => Ok(a
+                    .into_iter()
+                    .zip(b)
+                    .map(|(x, y)| Value::from([x, y]))
+                    .collect::<Vec<_>>()
+                    .into()),
+                (a, b) => crate::rt_error!(op: a, b => [zip]),
+            },
+            "windows" => |a, n, s| {
+                split_into(a, n, s, "windows", |len, n| {
+                    (0..(len + 1).saturating_sub(n)).map(|i| i..i + n).collect()
+                })
+            },
+            "chunks" => |a, n, s| {
+                split_into(a, n, s, "chunks", |len, n| {
+                    (0..len).step_by(n).map(|i| i..len.min(i + n)).collect()
+                })
+            },
+            "gcd" => |a, b, s| Value::gcd_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "lcm" => |a, b, s| Value::lcm_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "base" => |a, b, s| Value::base_with(a, b, s.overflow()).map_err(crate::Error::from),
+            "join" => |a, sep, _| match (a, sep) {
+                (Value::Array(a), sep @ (Value::Str(_) | Value::Char(_))) => {
+                    let sep = sep.into_string()?;
+                    let parts = a
+                        .into_iter()
+                        .map(Value::into_string)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(Value::from(parts.join(&sep)))
+                }
+                (a, sep) => crate::rt_error!(op: a, sep => [join]),
+            },
+            "starts" => |a, b, _| match (a, b) {
+                (Value::Str(s), Value::Str(prefix)) => Ok(s.starts_with(prefix.as_str()).into()),
+                (Value::Str(s), Value::Char(c)) => Ok(s.starts_with(c).into()),
+                (a, b) => crate::rt_error!(op: a, b => [starts]),
+            },
+            "ends" => |a, b, _| match (a, b) {
+                (Value::Str(s), Value::Str(suffix)) => Ok(s.ends_with(suffix.as_str()).into()),
+                (Value::Str(s), Value::Char(c)) => Ok(s.ends_with(c).into()),
+                (a, b) => crate::rt_error!(op: a, b => [ends]),
+            },
             _ => return Err(()),
         };
         Ok(Self(op, s.into()))
     }
 }
 
+/// Splits an array or string into the parts of it at the index `ranges` gives for its length and
+/// `n`, which must be positive.
+fn split_into(
+    a: Value,
+    n: Value,
+    s: &Stack,
+    op: &'static str,
+    ranges: fn(usize, usize) -> Vec<Range<usize>>,
+) -> Result<Value, crate::Error> {
+    let n = match n {
+        Value::Integer(n) if n > 0 => n as usize,
+        n => return Err(RuntimeError::InvalidOperation(vec![a, n], op).into()),
+    };
+    match a {
+        Value::Array(a) => Ok(ranges(a.len(), n)
+            .into_iter()
+            .map(|r| Value::from(a[r].to_vec()))
+            .collect::<Vec<_>>()
+            .into()),
+        Value::Str(string) => {
+            let units = s.segmentation().split(&string).collect::<Vec<_>>();
+            Ok(ranges(units.len(), n)
+                .into_iter()
+                .map(|r| Value::from(units[r].concat()))
+                .collect::<Vec<_>>()
+                .into())
+        }
+        a => Err(RuntimeError::InvalidOperation(vec![a, n.into()], op).into()),
+    }
+}
+
 impl Operator for BinaryOp {
     fn run(&self, stack: &mut Stack) -> Result<(), crate::Error> {
         let snd = stack.pop()?;
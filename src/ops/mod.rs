@@ -3,13 +3,18 @@ mod nullary;
 mod stack;
 mod ternary;
 mod unary;
+mod word;
 
 use crate::{
-    error::{Error, SyntaxError},
+    error::{Error, RuntimeError, SyntaxError},
     stack::{Stack, Value},
+    util::str_ext::{Span, StrExt, Token},
 };
+use itertools::Itertools;
 use std::{
-    fmt::{Debug, Display},
+    collections::HashSet,
+    fmt::{self, Debug, Display},
+    rc::Rc,
     str::FromStr,
 };
 
@@ -18,6 +23,7 @@ use nullary::Nullary;
 use stack::StackOp;
 use ternary::Ternary;
 use unary::UnaryOp;
+use word::Word;
 
 impl FromStr for Box<dyn Operator> {
     type Err = SyntaxError;
@@ -31,37 +37,178 @@ impl FromStr for Box<dyn Operator> {
             .or_else(|_| s.parse::<Nullary>().map(cast_box))
             .or_else(|_| s.parse::<Ternary>().map(cast_box))
             .or_else(|_| s.parse::<StackOp>().map(cast_box))
-            .map_err(|_| s.into())
     }
 }
 
-pub trait Operator: Display + Debug {
-    fn run_mut(&mut self, stack: &mut Stack) -> Result<(), crate::Error> {
-        self.run(stack)
-    }
+/// Whether `s` is the name of an operator, as opposed to a literal or a variable other than `A`
+/// to `Z`.
+pub fn is_operator(s: &str) -> bool {
+    s.parse::<BinaryOp>().is_ok()
+        || s.parse::<UnaryOp>().is_ok()
+        || s.parse::<Nullary>().is_ok()
+        || s.parse::<Ternary>().is_ok()
+        || s.parse::<StackOp>()
+            .is_ok_and(|op| !op.is_literal() && !op.is_named_variable())
+}
 
+pub trait Operator: Display + Debug {
     fn run(&self, stack: &mut Stack) -> Result<(), crate::Error>;
 
     fn as_str(&self) -> &str;
 }
 
-pub fn parse_and_execute<'s, I>(i: I, stack: &'_ mut Stack) -> Result<(), Error>
+/// An operator that remembers where in the source it came from, so that errors raised while
+/// running it can point back at it.
+struct Located(Box<dyn Operator>, Span);
+
+impl Operator for Located {
+    fn run(&self, stack: &mut Stack) -> Result<(), Error> {
+        self.0.run(stack).map_err(|e| e.at(self.1))
+    }
+
+    fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Display for Located {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for Located {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// The names a program can use besides the built-ins: the words and variables it defines, or
+/// that programs which ran before it on the same stack defined.
+#[derive(Debug, Default, Clone)]
+pub struct Names {
+    pub words: HashSet<String>,
+    pub variables: HashSet<String>,
+}
+
+impl Names {
+    /// Every word given a definition and every variable assigned anywhere in `tokens`, including
+    /// inside blocks and array literals. Tokens that don't scan are skipped, parsing reports them.
+    fn defined_in<'s>(tokens: impl IntoIterator<Item = Result<Token<'s>, Error>>) -> Self {
+        fn scan<'s>(tokens: impl IntoIterator<Item = Result<Token<'s>, Error>>, names: &mut Names) {
+            let mut tokens = tokens.into_iter().flatten();
+            while let Some(t) = tokens.next() {
+                match t.text.as_bytes() {
+                    b":" => names
+                        .words
+                        .extend(tokens.next().map(|name| name.text.to_string())),
+                    [b':', ..] => {
+                        let name = t.text.trim_start_matches(':');
+                        names.variables.insert(name.to_string());
+                    }
+                    [b'{' | b'[', ..] | [b'#', b'[', ..] => scan(t.inner(), names),
+                    _ => (),
+                }
+            }
+        }
+        let mut names = Self::default();
+        scan(tokens, &mut names);
+        names
+    }
+}
+
+/// Parses `i` into operators. Besides the built-ins, a token can name a word or a variable
+/// defined somewhere in `i` or one of the `known` ones.
+pub fn parse<'s, I>(i: I, known: &Names) -> Result<Vec<Rc<dyn Operator>>, Error>
 where
-    I: Iterator<Item = &'s str>,
+    I: IntoIterator<Item = Result<Token<'s>, Error>>,
+    I::IntoIter: Clone,
 {
-    i.map(str::parse::<Box<dyn Operator>>).try_for_each(|op| {
-        let mut op = op?;
-        #[cfg(debug_assertions)]
-        println!(
-            "{}{:?} apply `{}`",
-            " ".repeat(stack.indent),
-            stack.as_slice(),
-            op.as_str()
-        );
-
-        op.run_mut(stack)
-    })?;
-    Ok(())
+    let i = i.into_iter();
+    let mut names = Names::defined_in(i.clone());
+    names.words.extend(known.words.iter().cloned());
+    names.variables.extend(known.variables.iter().cloned());
+    parse_all(i, &names)
+}
+
+fn parse_all<'s, I>(i: I, names: &Names) -> Result<Vec<Rc<dyn Operator>>, Error>
+where
+    I: IntoIterator<Item = Result<Token<'s>, Error>>,
+{
+    let mut i = i.into_iter();
+    let mut ops = Vec::new();
+    while let Some(t) = i.next() {
+        ops.push(parse_one(t?, &mut i, names)?);
+    }
+    Ok(ops)
+}
+
+/// Parses the operator starting at `t`, taking the rest of it from `rest` if it is a definition.
+fn parse_one<'s, I>(t: Token<'s>, rest: &mut I, names: &Names) -> Result<Rc<dyn Operator>, Error>
+where
+    I: Iterator<Item = Result<Token<'s>, Error>>,
+{
+    if t.text == ":" {
+        word::parse_definition(t, rest, names)
+    } else {
+        parse_token(t, names)
+    }
+}
+
+/// Parses `code` into a block while running on `stack`, in its mode and knowing about the words
+/// and variables defined on it. Syntax errors become runtime errors, so that `try` can catch them.
+pub fn parse_block(code: &str, stack: &Stack) -> Result<Value, Error> {
+    match parse(code.split_tokens_in(stack.mode()), &stack.names()) {
+        Ok(ops) => Ok(Value::Block(ops)),
+        Err(Error::Syntax(e, span)) => Err(RuntimeError::Syntax(e, span).into()),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_token(t: Token<'_>, names: &Names) -> Result<Rc<dyn Operator>, Error> {
+    // Literals remember their source in a canonical form, one space between tokens and no
+    // comments, so that `quote` gives code that `eval` can read back.
+    let op: Box<dyn Operator> = match t.text.as_bytes() {
+        [b'{', .., b'}'] => {
+            let block = Value::Block(parse_all(t.inner(), names)?);
+            let source = block.literal().to_string();
+            Box::new(StackOp::literal(block, &source))
+        }
+        [b'[', .., b']'] => {
+            let ops = parse_all(t.inner(), names)?;
+            let source = delimited_source("[", &ops, "]");
+            Box::new(StackOp::array(ops, &source))
+        }
+        [b'#', b'[', .., b']'] => {
+            let ops = parse_all(t.inner(), names)?;
+            let source = delimited_source("#[", &ops, "]");
+            Box::new(StackOp::map(ops, &source))
+        }
+        _ => match t.text.parse() {
+            Ok(op) => op,
+            Err(SyntaxError::UnknownToken(_)) if names.words.contains(t.text) => {
+                Box::new(Word::new(t.text))
+            }
+            Err(SyntaxError::UnknownToken(_)) if names.variables.contains(t.text) => {
+                Box::new(StackOp::variable(t.text))
+            }
+            Err(e) => return Err(Error::from(e).at(t.span)),
+        },
+    };
+    Ok(Rc::new(Located(op, t.span)))
+}
+
+fn delimited_source(open: &str, ops: &[Rc<dyn Operator>], close: &str) -> String {
+    if ops.is_empty() {
+        format!("{} {}", open, close)
+    } else {
+        format!(
+            "{} {} {}",
+            open,
+            ops.iter().map(|o| o.as_str()).format(" "),
+            close
+        )
+    }
 }
 
 pub fn execute<I, O>(i: I, stack: &'_ mut Stack) -> Result<(), crate::Error>
@@ -84,12 +231,52 @@ where
     Ok(())
 }
 
+/// Executes the block `i` in a new scope, so that the new variables it assigns are gone once it ends.
+pub fn run_block<I, O>(i: I, stack: &mut Stack) -> Result<(), crate::Error>
+where
+    I: IntoIterator<Item = O>,
+    O: AsRef<dyn Operator>,
+{
+    stack.enter_call()?;
+    stack.enter_scope();
+    let result = execute(i, stack);
+    stack.leave_scope();
+    stack.leave_call();
+    result
+}
+
+/// Executes the block `i` for one element of `%`, `$` and the like, in a frame of its own: it
+/// still reads the outer variables, but assigning one makes a new local that is gone once it ends.
+pub fn run_for_element<I, O>(i: I, stack: &mut Stack) -> Result<(), crate::Error>
+where
+    I: IntoIterator<Item = O>,
+    O: AsRef<dyn Operator>,
+{
+    stack.enter_call()?;
+    stack.enter_block_frame();
+    let result = execute(i, stack);
+    stack.leave_frame();
+    stack.leave_call();
+    result
+}
+
+/// Executes `v` if it is a block, or pushes it otherwise.
+pub fn run_or_push(v: Value, stack: &mut Stack) -> Result<(), crate::Error> {
+    match v {
+        Value::Block(b) => run_block(&b, stack),
+        v => {
+            stack.push(v);
+            Ok(())
+        }
+    }
+}
+
 pub fn calculate<I, O>(input: Value, i: I, stack: &mut Stack) -> Result<Value, crate::Error>
 where
     I: IntoIterator<Item = O>,
     O: AsRef<dyn Operator>,
 {
     stack.push(input);
-    execute(i, stack)?;
+    run_for_element(i, stack)?;
     Ok(stack.take_as_value()?)
 }
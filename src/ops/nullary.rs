@@ -30,7 +30,7 @@ impl FromStr for Nullary {
                     if buf.ends_with('\n') {
                         buf.pop();
                     }
-                    s.push(Value::Str(buf));
+                    s.push(Value::from(buf));
                     Ok(())
                 },
                 s.into(),
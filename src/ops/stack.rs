@@ -1,12 +1,15 @@
 use super::Operator;
 use crate::{
-    error::runtime::*,
-    ops::{calculate, execute},
-    stack::{Stack, Value},
+    error::both::*,
+    ops::{calculate, execute, run_block, run_or_push},
+    stack::{Map, Stack, Value},
+    util::text::unit_value,
 };
+use itertools::Itertools;
 use std::{
     fmt::{self, Debug, Display},
     mem::take,
+    rc::Rc,
     str::{from_utf8, FromStr},
 };
 
@@ -14,13 +17,46 @@ pub struct StackOp(Enum, String);
 
 enum Enum {
     Simple(fn(&mut Stack<'_>) -> Result<(), crate::Error>),
-    Push,
+    Push(Value),
+    Array(Vec<Rc<dyn Operator>>),
+    Map(Vec<Rc<dyn Operator>>),
     Nth(usize, fn(&mut Stack<'_>, usize) -> RuntimeResult<()>),
-    VarAccess(char, fn(&mut Stack<'_>, char) -> RuntimeResult<()>),
+    VarAccess(String, fn(&mut Stack<'_>, &str) -> RuntimeResult<()>),
+}
+
+impl StackOp {
+    pub fn literal(v: Value, s: &str) -> Self {
+        Self(Enum::Push(v), s.into())
+    }
+
+    pub fn array(ops: Vec<Rc<dyn Operator>>, s: &str) -> Self {
+        Self(Enum::Array(ops), s.into())
+    }
+
+    pub fn map(ops: Vec<Rc<dyn Operator>>, s: &str) -> Self {
+        Self(Enum::Map(ops), s.into())
+    }
+
+    /// Reads the variable `name`.
+    pub fn variable(name: &str) -> Self {
+        Self(
+            Enum::VarAccess(name.into(), |s, v| s.push_var(v)),
+            name.into(),
+        )
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(self.0, Enum::Push(_) | Enum::Array(_) | Enum::Map(_))
+    }
+
+    /// Whether this reads or assigns a variable with a longer name than `A` to `Z`.
+    pub fn is_named_variable(&self) -> bool {
+        matches!(&self.0, Enum::VarAccess(name, _) if name.chars().nth(1).is_some())
+    }
 }
 
 impl FromStr for StackOp {
-    type Err = ();
+    type Err = SyntaxError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let e = match s.as_bytes() {
             b";" => Ok(Enum::Simple(|s| {
@@ -31,13 +67,13 @@ impl FromStr for StackOp {
                 let len = s.len();
                 s.get_mut((len - 2)..len)
                     .map(|slice| slice.rotate_left(1))
-                    .ok_or(crate::Error::Runtime(RuntimeError::StackEmpty))
+                    .ok_or_else(|| RuntimeError::StackEmpty.into())
             })),
             b"@" => Ok(Enum::Simple(|s| {
                 let len = s.len();
                 s.get_mut((len - 3)..len)
                     .map(|slice| slice.rotate_left(1))
-                    .ok_or(crate::Error::Runtime(RuntimeError::StackEmpty))
+                    .ok_or_else(|| RuntimeError::StackEmpty.into())
             })),
             b"(" => Ok(Enum::Simple(|s| {
                 let top = match s.pop()? {
@@ -53,19 +89,19 @@ impl FromStr for StackOp {
                             Ok(v)
                         }
                     }
-                    Value::Str(mut st) => {
-                        if st.is_empty() {
-                            Err(RuntimeError::InvalidOperation(
-                                vec![st.into()],
-                                "remove head",
-                            ))
-                        } else {
-                            let v = st.remove(0);
+                    Value::Str(mut st) => match s.segmentation().offset(&st, 1) {
+                        Some(at) if !st.is_empty() => {
+                            let v = unit_value(&st[..at]);
+                            drop(st.drain(..at));
                             s.push(st.into());
-                            Ok(v.into())
+                            Ok(v)
                         }
-                    }
-                    x => x - Value::Integer(1),
+                        _ => Err(RuntimeError::InvalidOperation(
+                            vec![st.into()],
+                            "remove head",
+                        )),
+                    },
+                    x => x.sub_with(Value::Integer(1), s.overflow()),
                 };
                 s.push(top?);
                 Ok(())
@@ -83,18 +119,19 @@ impl FromStr for StackOp {
                             ))
                         }
                     }
-                    Value::Str(mut st) => {
-                        if let Some(v) = st.pop() {
+                    Value::Str(mut st) => match s.segmentation().offset_back(&st, 1) {
+                        Some(at) => {
+                            let v = unit_value(&st[at..]);
+                            st.truncate(at);
                             s.push(st.into());
-                            Ok(v.into())
-                        } else {
-                            Err(RuntimeError::InvalidOperation(
-                                vec![st.into()],
-                                "remove last",
-                            ))
+                            Ok(v)
                         }
-                    }
-                    x => x + Value::Integer(1),
+                        None => Err(RuntimeError::InvalidOperation(
+                            vec![st.into()],
+                            "remove last",
+                        )),
+                    },
+                    x => x.add_with(Value::Integer(1), s.overflow()),
                 };
                 s.push(top?);
                 Ok(())
@@ -104,7 +141,7 @@ impl FromStr for StackOp {
                 if let Value::Block(b) = v {
                     let mut i = 0;
                     while {
-                        execute(&b, s)?;
+                        run_block(&b, s)?;
                         s.pop()?.into()
                     } {
                         i += 1;
@@ -117,11 +154,46 @@ impl FromStr for StackOp {
                     crate::rt_error!(op: v => [while])
                 }
             })),
-            [v @ b'A'..=b'Z'] => Ok(Enum::VarAccess(*v as _, |s, v| {
-                s.push_var(v);
-                Ok(())
+            b"e&" => Ok(Enum::Simple(|s| {
+                let rhs = s.pop()?;
+                let lhs = s.pop()?;
+                if bool::from(&lhs) {
+                    run_or_push(rhs, s)
+                } else {
+                    s.push(lhs);
+                    Ok(())
+                }
+            })),
+            b"e|" => Ok(Enum::Simple(|s| {
+                let rhs = s.pop()?;
+                let lhs = s.pop()?;
+                if bool::from(&lhs) {
+                    s.push(lhs);
+                    Ok(())
+                } else {
+                    run_or_push(rhs, s)
+                }
+            })),
+            b"try" => Ok(Enum::Simple(|s| {
+                let handler = s.pop()?;
+                let body = s.pop()?;
+                let before = s.as_slice().to_vec();
+                match run_or_push(body, s) {
+                    Err(crate::Error::Runtime(e, _)) => {
+                        s.restore(before);
+                        s.push(e.into_value());
+                        run_or_push(handler, s)
+                    }
+                    result => result,
+                }
             })),
-            [b':', v @ b'A'..=b'Z'] => Ok(Enum::VarAccess(*v as _, |s, v| s.pop_var(v))),
+            b"throw" => Ok(Enum::Simple(|s| Err(RuntimeError::Thrown(s.pop()?).into()))),
+            [b'A'..=b'Z'] => Ok(Enum::VarAccess(s.into(), |s, v| s.push_var(v))),
+            [b':', b':', ..] => variable_name(&s[2..])
+                .map(|v| Enum::VarAccess(v.into(), |s, v| s.pop_global_var(v))),
+            [b':', ..] => {
+                variable_name(&s[1..]).map(|v| Enum::VarAccess(v.into(), |s, v| s.pop_var(v)))
+            }
             b"$" => Ok(Enum::Simple(|s| {
                 let top = s.pop()?;
                 if let Value::Integer(i) = top {
@@ -161,42 +233,64 @@ impl FromStr for StackOp {
                             .ok_or_else(|| RuntimeError::OutOfBounds(s.len(), n as _))
                     }))
                 } else {
-                    Err(())
+                    Err(s.into())
                 }
             }
-            _ => Ok(Enum::Push),
+            _ => s.parse().map(Enum::Push),
         };
         e.map(|e| Self(e, s.into()))
     }
 }
 
+/// Checks that `name` can be assigned to: `A` to `Z`, or a letter followed by letters, digits and
+/// underscores that isn't already an operator.
+fn variable_name(name: &str) -> Result<&str, SyntaxError> {
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(char::is_alphabetic)
+        || !chars.all(|c| c.is_alphanumeric() || c == '_')
+    {
+        Err(format!(":{}", name).into())
+    } else if matches!(name.as_bytes(), [b'A'..=b'Z']) {
+        Ok(name)
+    } else if super::is_operator(name) {
+        Err(SyntaxError::Builtin(name.into()))
+    } else {
+        Ok(name)
+    }
+}
+
 impl Operator for StackOp {
     fn run(&self, stack: &mut Stack) -> Result<(), crate::Error> {
         match &self.0 {
             Enum::Simple(f) => f(stack),
-            Enum::Push => {
-                let v = Value::from_str(&self.1, stack)?;
-                stack.push(v);
+            Enum::Push(v) => {
+                match stack.overflow().limit(v.clone()) {
+                    Some(v) => stack.push(v),
+                    None => crate::rt_error!(convert: v.clone(), i64),
+                }
                 Ok(())
             }
-            Enum::VarAccess(v, f) => f(stack, *v).map_err(crate::Error::from),
-            Enum::Nth(n, f) => f(stack, *n).map_err(crate::Error::from),
-        }
-        .map_err(Into::into)
-    }
-
-    fn run_mut(&mut self, stack: &mut Stack) -> Result<(), crate::Error> {
-        match &mut self.0 {
-            Enum::Simple(f) => f(stack),
-            Enum::Push => {
-                let v = Value::from_str(&self.1, stack)?;
-                stack.push(v);
+            Enum::Array(ops) => {
+                let mut sub_stack = stack.sub_stack();
+                execute(ops, &mut sub_stack)?;
+                let array = sub_stack.into_vec();
+                stack.push(Value::from(array));
+                Ok(())
+            }
+            Enum::Map(ops) => {
+                let mut sub_stack = stack.sub_stack();
+                execute(ops, &mut sub_stack)?;
+                let entries = sub_stack.into_vec();
+                if !entries.len().is_multiple_of(2) {
+                    crate::rt_error!(op: entries => [map_literal])
+                }
+                let map = entries.into_iter().tuples().collect::<Map>();
+                stack.push(Value::Map(map));
                 Ok(())
             }
-            Enum::VarAccess(v, f) => f(stack, *v).map_err(crate::Error::from),
+            Enum::VarAccess(v, f) => f(stack, v).map_err(crate::Error::from),
             Enum::Nth(n, f) => f(stack, *n).map_err(crate::Error::from),
         }
-        .map_err(Into::into)
     }
 
     fn as_str(&self) -> &str {
@@ -1,45 +1,123 @@
 use super::Operator;
-use crate::stack::Stack;
+use crate::{
+    ops::{calculate, run_or_push},
+    stack::{
+        value::{Value, MAX_REPEAT_LEN},
+        Stack,
+    },
+};
 use std::{
     fmt::{self, Debug, Display},
     str::FromStr,
 };
 
-pub struct Ternary(&'static str);
+pub struct Ternary(
+    fn(Value, Value, Value, &mut Stack) -> Result<(), crate::Error>,
+    String,
+);
 
 impl FromStr for Ternary {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "?" => Ok(Self("?")),
-            _ => Err(()),
-        }
+        let op: fn(Value, Value, Value, &mut Stack) -> Result<(), crate::Error> = match s {
+            "?" => |cond, then, elze, s| run_or_push(if cond.into() { then } else { elze }, s),
+            "replace" => |a, from, to, s| match (a, from, to) {
+                (Value::Str(a), Value::Str(from), to @ (Value::Str(_) | Value::Char(_)))
+                    if !from.is_empty() =>
+                {
+                    s.push(Value::from(a.replace(from.as_str(), &to.into_string()?)));
+                    Ok(())
+                }
+                (a, from, to) => crate::rt_error!(op: a, from, to => [replace]),
+            },
+            "m+" => |m, key, value, s| match m {
+                Value::Map(mut m) => {
+                    m.insert(key, value);
+                    s.push(m.into());
+                    Ok(())
+                }
+                m => crate::rt_error!(op: m, key, value => [insert]),
+            },
+            "zipwith" => |a, b, f, s| match (a, b, f) {
+                (Value::Array(a), Value::Array(b), Value::Block(f)) => {
+                    let zipped = a
+                        .into_iter()
+                        .zip(b)
+                        .map(|(x, y)| {
+                            let mut temp_stack = s.sub_stack();
+                            temp_stack.push(x);
+                            calculate(y, &f, &mut temp_stack)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    s.push(zipped.into());
+                    Ok(())
+                }
+                (a, b, f) => crate::rt_error!(op: a, b, f => [zipwith]),
+            },
+            "padl" => |a, width, fill, s| {
+                let (a, pad) = padding(a, width, fill, s)?;
+                s.push(Value::from(pad + &a));
+                Ok(())
+            },
+            "padr" => |a, width, fill, s| {
+                let (a, pad) = padding(a, width, fill, s)?;
+                s.push(Value::from(a + &pad));
+                Ok(())
+            },
+            _ => return Err(()),
+        };
+        Ok(Self(op, s.into()))
     }
 }
 
+/// The string to pad and the padding that makes it `width` characters long, repeating `fill`.
+fn padding(
+    a: Value,
+    width: Value,
+    fill: Value,
+    s: &Stack,
+) -> Result<(String, String), crate::Error> {
+    let a = match a {
+        Value::Str(a) => a.into_inner(),
+        a @ (Value::Char(_) | Value::Integer(_) | Value::BigInt(_)) => a.into_string()?,
+        a => crate::rt_error!(op: a, width, fill => [pad]),
+    };
+    let (width, fill) = match (width, fill) {
+        (Value::Integer(w), Value::Char(c)) if (0..=MAX_REPEAT_LEN as i64).contains(&w) => {
+            (w as usize, c.to_string())
+        }
+        (Value::Integer(w), Value::Str(f))
+            if (0..=MAX_REPEAT_LEN as i64).contains(&w) && s.segmentation().len(&f) == 1 =>
+        {
+            (w as usize, f.into_inner())
+        }
+        (width, fill) => crate::rt_error!(op: a, width, fill => [pad]),
+    };
+    let missing = width.saturating_sub(s.segmentation().len(&a));
+    Ok((a, fill.repeat(missing)))
+}
+
 impl Operator for Ternary {
     fn run(&self, stack: &mut Stack) -> Result<(), crate::Error> {
-        let elze = stack.pop()?;
-        let then = stack.pop()?;
-        let cond = stack.pop()?;
-        let v = if cond.into() { then } else { elze };
-        stack.push(v);
-        Ok(())
+        let c = stack.pop()?;
+        let b = stack.pop()?;
+        let a = stack.pop()?;
+        self.0(a, b, c, stack)
     }
 
     fn as_str(&self) -> &str {
-        self.0
+        &self.1
     }
 }
 
 impl Display for Ternary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.0)
+        f.write_str(&self.1)
     }
 }
 
 impl Debug for Ternary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.0)
+        f.write_str(&self.1)
     }
 }
@@ -1,8 +1,9 @@
 use super::Operator;
 use crate::{
     error::runtime::*,
-    ops::{calculate, execute},
+    ops::{calculate, parse_block, run_block, run_or_push},
     stack::{value::Value, Stack},
+    util::text::unit_value,
 };
 use std::{
     fmt::{self, Debug, Display},
@@ -27,9 +28,11 @@ impl FromStr for UnaryOp {
                 "_" => Enum::Calculate(|v| Ok(v.clone())),
                 "~" => Enum::TransformStar(|x, s| match x {
                     Value::Integer(i) => Ok(vec![Value::Integer(!i)]),
-                    Value::Array(a) => Ok(a),
+                    Value::BigInt(b) => Ok(vec![Value::from(!b)]),
+                    Value::Array(a) => Ok(a.into_inner()),
+                    Value::Map(m) => Ok(m.into_iter().map(|(k, v)| Value::from([k, v])).collect()),
                     Value::Block(b) => {
-                        execute(b, s)?;
+                        run_block(b, s)?;
                         Ok(vec![])
                     }
                     _ => crate::rt_error!(op: x => [bit_not_or_spread]),
@@ -37,13 +40,14 @@ impl FromStr for UnaryOp {
                 "!" => Enum::Transform(|x| Ok((!bool::from(&x)).into())),
                 "c" => Enum::Transform(Value::to_char),
                 "f" => Enum::Transform(Value::to_float),
-                "i" => Enum::Transform(Value::to_int),
+                "i" => Enum::TransformStack(|x, s| Ok(x.to_int_with(s.overflow())?)),
                 "s" => Enum::Transform(Value::to_str),
                 "p" => Enum::Borrow(|x| println!("{}", x)),
                 "," => Enum::TransformStack(|x, s| match x {
                     Value::Integer(i) => Ok((0..i).map(Value::from).collect::<Vec<_>>().into()),
                     Value::Array(a) => Ok(a.len().into()),
-                    Value::Str(s) => Ok(s.len().into()),
+                    Value::Str(string) => Ok(s.segmentation().len(&string).into()),
+                    Value::Map(m) => Ok(m.len().into()),
                     Value::Block(b) => match s.pop()? {
                         Value::Array(mut a) => {
                             let mut indexes = Vec::with_capacity(a.len());
@@ -67,33 +71,109 @@ impl FromStr for UnaryOp {
                             debug_assert!(indexes.is_empty());
                             Ok(Value::Array(a))
                         }
-                        Value::Str(mut string) => {
+                        Value::Str(string) => {
+                            let segmentation = s.segmentation();
                             let mut temp_stack = s.sub_stack();
-                            let mut indexes = Vec::with_capacity(string.len());
-                            for (i, c) in string.char_indices().rev() {
-                                if calculate(Value::Char(c), &b, &mut temp_stack)?.into() {
-                                    indexes.push(i);
+                            let mut kept = Vec::new();
+                            for u in segmentation.split(&string).rev() {
+                                if calculate(unit_value(u), &b, &mut temp_stack)?.into() {
+                                    kept.push(u);
                                 }
                             }
-                            let mut idx = 0;
-                            string.retain(|_| {
-                                let keep = match indexes.last() {
-                                    Some(i) if *i == idx => {
-                                        indexes.pop();
-                                        true
-                                    }
-                                    _ => false,
-                                };
-                                idx += 1;
-                                keep
-                            });
-                            debug_assert!(indexes.is_empty());
-                            Ok(Value::Str(string))
+                            Ok(Value::from(kept.into_iter().rev().collect::<String>()))
                         }
                         x => crate::rt_error!(op: x => [filter]),
                     },
                     x => crate::rt_error!(op: x => [length_range]),
                 }),
+                "eval" => Enum::TransformStar(|x, s| {
+                    match x {
+                        Value::Str(code) => run_or_push(parse_block(&code, s)?, s)
+                            .map_err(crate::Error::unlocated)?,
+                        Value::Block(b) => run_block(&b, s)?,
+                        x => crate::rt_error!(op: x => [eval]),
+                    }
+                    Ok(vec![])
+                }),
+                "unquote" => Enum::TransformStack(|x, s| match x {
+                    Value::Str(code) => parse_block(&code, s),
+                    x => crate::rt_error!(op: x => [unquote]),
+                }),
+                "quote" => Enum::Transform(|x| Ok(x.quote())),
+                "u" => Enum::TransformStack(|x, s| Ok(x.unique_with(s.segmentation())?)),
+                "sqrt" => Enum::Transform(Value::sqrt),
+                "sin" => Enum::Transform(Value::sin),
+                "cos" => Enum::Transform(Value::cos),
+                "tan" => Enum::Transform(Value::tan),
+                "asin" => Enum::Transform(Value::asin),
+                "acos" => Enum::Transform(Value::acos),
+                "atan" => Enum::Transform(Value::atan),
+                "ln" => Enum::Transform(Value::ln),
+                "log" => Enum::Transform(Value::log),
+                "exp" => Enum::Transform(Value::exp),
+                "abs" => Enum::TransformStack(|x, s| Ok(x.abs_with(s.overflow())?)),
+                "sign" => Enum::Transform(Value::sign),
+                "floor" => Enum::TransformStack(|x, s| Ok(x.floor_with(s.overflow())?)),
+                "ceil" => Enum::TransformStack(|x, s| Ok(x.ceil_with(s.overflow())?)),
+                "round" => Enum::TransformStack(|x, s| Ok(x.round_with(s.overflow())?)),
+                "prime" => Enum::Transform(Value::prime),
+                "factor" => Enum::Transform(Value::factor),
+                "bin" => Enum::Transform(|x| x.to_radix(2, "bin")),
+                "oct" => Enum::Transform(|x| x.to_radix(8, "oct")),
+                "hex" => Enum::Transform(|x| x.to_radix(16, "hex")),
+                "upper" => Enum::Transform(|x| match x {
+                    Value::Str(s) => Ok(Value::from(s.to_uppercase())),
+                    Value::Char(c) => Ok(unit_value(&c.to_uppercase().collect::<String>())),
+                    x => crate::rt_error!(op: x => [upper]),
+                }),
+                "lower" => Enum::Transform(|x| match x {
+                    Value::Str(s) => Ok(Value::from(s.to_lowercase())),
+                    Value::Char(c) => Ok(unit_value(&c.to_lowercase().collect::<String>())),
+                    x => crate::rt_error!(op: x => [lower]),
+                }),
+                "trim" => Enum::Transform(|x| match x {
+                    Value::Str(s) => Ok(Value::from(s.trim())),
+                    x => crate::rt_error!(op: x => [trim]),
+                }),
+                "reverse" => Enum::TransformStack(|x, s| match x {
+                    Value::Str(string) => Ok(Value::from(
+                        s.segmentation().split(&string).rev().collect::<String>(),
+                    )),
+                    Value::Array(mut a) => {
+                        a.reverse();
+                        Ok(Value::Array(a))
+                    }
+                    x => crate::rt_error!(op: x => [reverse]),
+                }),
+                "transpose" => Enum::Transform(|x| {
+                    let Value::Array(rows) = &x else {
+                        crate::rt_error!(op: x => [transpose]);
+                    };
+                    let width = match rows.first() {
+                        Some(Value::Array(row)) => row.len(),
+                        _ => 0,
+                    };
+                    let mut columns = vec![Vec::with_capacity(rows.len()); width];
+                    for row in rows.iter() {
+                        match row {
+                            Value::Array(row) if row.len() == width => {
+                                for (column, v) in columns.iter_mut().zip(row.iter()) {
+                                    column.push(v.clone());
+                                }
+                            }
+                            _ => crate::rt_error!(op: x => [transpose]),
+                        }
+                    }
+                    Ok(columns.into())
+                }),
+                "mk" => Enum::Transform(|x| match x {
+                    Value::Map(m) => Ok(Value::Array(m.into_iter().map(|(k, _)| k).collect())),
+                    x => crate::rt_error!(op: x => [keys]),
+                }),
+                "mv" => Enum::Transform(|x| match x {
+                    Value::Map(m) => Ok(Value::Array(m.into_iter().map(|(_, v)| v).collect())),
+                    x => crate::rt_error!(op: x => [values]),
+                }),
                 "S/" => Enum::Transform(|x| {
                     if let Value::Str(s) = x {
                         Ok(Value::Array(
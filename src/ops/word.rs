@@ -0,0 +1,138 @@
+use super::{execute, is_operator, parse_all, Located, Names, Operator};
+use crate::{
+    error::{Error, RuntimeError, SyntaxError},
+    stack::{Stack, Value},
+    util::str_ext::Token,
+};
+use itertools::Itertools;
+use std::{
+    fmt::{self, Debug, Display},
+    rc::Rc,
+};
+
+/// A call to a word defined with `: name { ... }`.
+///
+/// The name is looked up when the call runs, not when it is parsed, so a word can call itself
+/// and sees the latest definition of every word it uses.
+pub struct Word(String);
+
+impl Word {
+    pub fn new(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+impl Operator for Word {
+    fn run(&self, stack: &mut Stack) -> Result<(), Error> {
+        let body = stack
+            .word(&self.0)
+            .ok_or_else(|| RuntimeError::UndefinedWord(self.0.clone()))?;
+        stack.enter_call()?;
+        stack.enter_frame();
+        let result = execute(body.iter(), stack);
+        stack.leave_frame();
+        stack.leave_call();
+        result
+    }
+
+    fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A definition, `: name { ... }`, which (re)defines the word `name` when it runs.
+struct Define {
+    name: String,
+    body: Rc<[Rc<dyn Operator>]>,
+    text: String,
+}
+
+impl Operator for Define {
+    fn run(&self, stack: &mut Stack) -> Result<(), Error> {
+        stack.define_word(&self.name, self.body.clone());
+        Ok(())
+    }
+
+    fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for Define {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Debug for Define {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parses the rest of a definition, whose `:` has already been read: the name and the block
+/// with the body.
+pub(super) fn parse_definition<'s, I>(
+    colon: Token<'s>,
+    tokens: &mut I,
+    names: &Names,
+) -> Result<Rc<dyn Operator>, Error>
+where
+    I: Iterator<Item = Result<Token<'s>, Error>>,
+{
+    let unclosed = || Error::from(SyntaxError::Unclosed(":")).at(colon.span);
+    let name = tokens.next().ok_or_else(unclosed)??;
+    check_name(name.text).map_err(|e| Error::from(e).at(name.span))?;
+    let expected_body = |span| Error::from(SyntaxError::ExpectedBody(name.text.into())).at(span);
+    let block = match tokens.next() {
+        Some(t) => t?,
+        None => return Err(expected_body(name.span)),
+    };
+    if !(block.text.starts_with('{') && block.text.ends_with('}')) {
+        return Err(expected_body(block.span));
+    }
+    let body = parse_all(block.inner(), names)?;
+    let end = block.span.end;
+    let text = if body.is_empty() {
+        format!(": {} {{ }}", name.text)
+    } else {
+        format!(
+            ": {} {{ {} }}",
+            name.text,
+            body.iter().map(|o| o.as_str()).join(" ")
+        )
+    };
+    let define = Define {
+        name: name.text.into(),
+        body: body.into(),
+        text,
+    };
+    let mut span = colon.span;
+    span.end = end;
+    Ok(Rc::new(Located(Box::new(define), span)))
+}
+
+fn check_name(name: &str) -> Result<(), SyntaxError> {
+    if is_operator(name) {
+        Err(SyntaxError::Builtin(name.into()))
+    } else if name.starts_with([':', '[', '{'])
+        || name.starts_with("#[")
+        || !matches!(name.parse::<Value>(), Err(SyntaxError::UnknownToken(_)))
+    {
+        Err(SyntaxError::InvalidWordName(name.into()))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    error::Error,
+    ops::{self, Names, Operator},
+    stack::{Stack, Value},
+    util::str_ext::{Mode, StrExt},
+};
+use std::{io::BufRead, rc::Rc, str::FromStr};
+
+/// A parsed program, ready to be run any number of times.
+///
+/// Parsing resolves every token (including the contents of blocks and array literals) into an
+/// operator up front, so any syntax error is reported before anything runs. The program remembers
+/// the mode it was written in, so that the code it gives to `eval` is read the same way.
+#[derive(Clone, Debug)]
+pub struct Program(Vec<Rc<dyn Operator>>, Mode);
+
+impl Program {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(Self(
+            ops::parse(s.split_tokens(), &Names::default())?,
+            Mode::default(),
+        ))
+    }
+
+    pub fn parse_with_mode(s: &str, mode: Mode) -> Result<Self, Error> {
+        Self::parse_for(s, mode, &Names::default())
+    }
+
+    /// Parses `s` to run on a stack where the `known` words have already been defined.
+    pub(crate) fn parse_for(s: &str, mode: Mode, known: &Names) -> Result<Self, Error> {
+        Ok(Self(ops::parse(s.split_tokens_in(mode), known)?, mode))
+    }
+
+    pub fn run(&self, stack: &mut Stack<'_>) -> Result<(), Error> {
+        stack.set_mode(self.1);
+        ops::execute(&self.0, stack)
+    }
+
+    pub fn run_with_input(&self, i: &mut dyn BufRead) -> Result<Vec<Value>, Error> {
+        let mut stack = Stack::with_input(i);
+        self.run(&mut stack)?;
+        Ok(stack.into_vec())
+    }
+}
+
+impl FromStr for Program {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
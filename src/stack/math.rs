@@ -0,0 +1,158 @@
+use super::Value;
+use crate::{error::runtime::*, util::overflow::Overflow};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive};
+use std::cmp::Ordering;
+
+impl Value {
+    /// The value of a number as a float, if it is one.
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(*i as f64),
+            Value::BigInt(b) => b.to_f64(),
+            Value::Rational(r) => r.to_f64(),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Applies `f` to a number as a float. Numbers outside the domain where `defined` holds, or
+    /// for which `f` gives NaN, are errors.
+    fn real(
+        self,
+        op: &'static str,
+        f: fn(f64) -> f64,
+        defined: fn(f64) -> bool,
+    ) -> RuntimeResult<Value> {
+        let Some(x) = self.as_f64() else {
+            return Err(RuntimeError::InvalidOperation(vec![self], op));
+        };
+        match f(x) {
+            y if defined(x) && !y.is_nan() => Ok(Value::Float(y)),
+            _ => Err(RuntimeError::Domain(vec![self], op)),
+        }
+    }
+
+    pub fn sqrt(self) -> RuntimeResult<Value> {
+        self.real("sqrt", f64::sqrt, |x| x >= 0.0)
+    }
+
+    pub fn sin(self) -> RuntimeResult<Value> {
+        self.real("sin", f64::sin, f64::is_finite)
+    }
+
+    pub fn cos(self) -> RuntimeResult<Value> {
+        self.real("cos", f64::cos, f64::is_finite)
+    }
+
+    pub fn tan(self) -> RuntimeResult<Value> {
+        self.real("tan", f64::tan, f64::is_finite)
+    }
+
+    pub fn asin(self) -> RuntimeResult<Value> {
+        self.real("asin", f64::asin, |x| (-1.0..=1.0).contains(&x))
+    }
+
+    pub fn acos(self) -> RuntimeResult<Value> {
+        self.real("acos", f64::acos, |x| (-1.0..=1.0).contains(&x))
+    }
+
+    pub fn atan(self) -> RuntimeResult<Value> {
+        self.real("atan", f64::atan, |_| true)
+    }
+
+    /// The natural logarithm.
+    pub fn ln(self) -> RuntimeResult<Value> {
+        self.real("ln", f64::ln, |x| x > 0.0)
+    }
+
+    /// The base 10 logarithm.
+    pub fn log(self) -> RuntimeResult<Value> {
+        self.real("log", f64::log10, |x| x > 0.0)
+    }
+
+    pub fn exp(self) -> RuntimeResult<Value> {
+        self.real("exp", f64::exp, |_| true)
+    }
+
+    /// Raises a number to a float or rational power, or a float to any power, as floats.
+    pub(super) fn powf(self, other: Value) -> RuntimeResult<Value> {
+        let (Some(x), Some(y)) = (self.as_f64(), other.as_f64()) else {
+            return Err(RuntimeError::InvalidOperation(vec![self, other], "pow"));
+        };
+        match x.powf(y) {
+            p if p.is_nan() && !x.is_nan() && !y.is_nan() => {
+                Err(RuntimeError::Domain(vec![self, other], "pow"))
+            }
+            p => Ok(Value::Float(p)),
+        }
+    }
+
+    pub fn abs_with(self, overflow: Overflow) -> RuntimeResult<Value> {
+        match self {
+            Value::Integer(i) => match i.checked_abs() {
+                Some(a) => Ok(Value::Integer(a)),
+                None => match overflow.integer(BigInt::from(i).abs()) {
+                    Some(v) => Ok(v),
+                    None => crate::rt_error!(op: i => [abs]),
+                },
+            },
+            Value::BigInt(b) => overflow
+                .integer(b.abs())
+                .ok_or_else(|| RuntimeError::InvalidOperation(vec![b.into()], "abs")),
+            Value::Rational(r) => Ok(r.abs().into()),
+            Value::Float(f) => Ok(Value::Float(f.abs())),
+            x => crate::rt_error!(op: x => [abs]),
+        }
+    }
+
+    /// `-1`, `0` or `1`, as the number is negative, zero or positive.
+    pub fn sign(self) -> RuntimeResult<Value> {
+        match self {
+            Value::Float(f) if f.is_nan() => Err(RuntimeError::Domain(vec![self], "sign")),
+            Value::Integer(_) | Value::BigInt(_) | Value::Rational(_) | Value::Float(_) => {
+                Ok(Value::Integer(match self.cmp(&Value::Integer(0)) {
+                    Ordering::Less => -1,
+                    Ordering::Equal => 0,
+                    Ordering::Greater => 1,
+                }))
+            }
+            x => crate::rt_error!(op: x => [sign]),
+        }
+    }
+
+    /// Rounds a number to an integer, with `ratio` for rationals and `float` for floats, and
+    /// handles integers that don't fit in an `i64` according to `overflow`.
+    fn rounded(
+        self,
+        op: &'static str,
+        overflow: Overflow,
+        ratio: fn(&BigRational) -> BigRational,
+        float: fn(f64) -> f64,
+    ) -> RuntimeResult<Value> {
+        let rounded = match &self {
+            Value::Integer(_) | Value::BigInt(_) => self.clone(),
+            Value::Rational(r) => ratio(r).to_integer().into(),
+            Value::Float(f) if f.is_finite() => Value::Float(float(*f)).to_int()?,
+            _ if self.as_f64().is_some() => return Err(RuntimeError::Domain(vec![self], op)),
+            _ => return Err(RuntimeError::InvalidOperation(vec![self], op)),
+        };
+        overflow
+            .limit(rounded)
+            .ok_or(RuntimeError::InvalidOperation(vec![self], op))
+    }
+
+    pub fn floor_with(self, overflow: Overflow) -> RuntimeResult<Value> {
+        self.rounded("floor", overflow, |r| r.floor(), f64::floor)
+    }
+
+    pub fn ceil_with(self, overflow: Overflow) -> RuntimeResult<Value> {
+        self.rounded("ceil", overflow, |r| r.ceil(), f64::ceil)
+    }
+
+    /// Rounds to the nearest integer, and halfway cases away from zero.
+    pub fn round_with(self, overflow: Overflow) -> RuntimeResult<Value> {
+        self.rounded("round", overflow, |r| r.round(), f64::round)
+    }
+}
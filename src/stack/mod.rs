@@ -1,40 +1,177 @@
+pub mod map;
+mod math;
+mod number_theory;
+pub mod shared;
 pub mod value;
 
-use crate::error::runtime::*;
-use std::{cell::RefCell, fmt, io::BufRead, ops, rc::Rc, slice::SliceIndex};
+use crate::{
+    error::runtime::*,
+    ops::{Names, Operator},
+    util::{overflow::Overflow, str_ext::Mode, text::Segmentation},
+};
+pub use map::Map;
+pub use shared::Shared;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    io::BufRead,
+    rc::Rc,
+    slice::SliceIndex,
+};
 pub use value::Value;
 
+/// The variables, in nested scopes: the globals, which start out with `A` to `Z`, and then one
+/// scope for each block or word that is running.
+///
+/// The scopes are grouped in frames. Each word call starts a frame that only sees its own scopes
+/// and the globals, so a word doesn't see the variables of whoever called it. Each block run for
+/// the elements of `%`, `$`, `,` and the like starts a frame that sees every outer variable but
+/// can't write to them. Reading a variable finds it in the innermost visible scope that has it.
 #[derive(Debug)]
-pub struct Variables([Value; (b'Z' - b'A') as usize + 1]);
+pub struct Variables {
+    scopes: Vec<HashMap<String, Value>>,
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// The index of the frame's first scope.
+    start: usize,
+    /// Whether the frame is a word call, which doesn't see the scopes before it.
+    word: bool,
+}
 
 impl Default for Variables {
     fn default() -> Self {
-        let mut vs = Self(Default::default());
-        vs['A'] = Value::Integer(10);
-        vs['B'] = Value::Integer(11);
-        vs['C'] = Value::Integer(12);
-        vs['D'] = Value::Integer(13);
-        vs['E'] = Value::Integer(14);
-        vs['F'] = Value::Integer(15);
-        vs['N'] = Value::Char('\n');
-        vs['S'] = Value::Char(' ');
-        vs['X'] = Value::Integer(0);
-        vs['Y'] = Value::Integer(1);
-        vs['Z'] = Value::Integer(2);
+        let mut vs = Self {
+            scopes: vec![('A'..='Z')
+                .map(|v| (v.to_string(), Value::Integer(0)))
+                .collect()],
+            frames: vec![Frame {
+                start: 0,
+                word: true,
+            }],
+        };
+        vs.set_global("A", Value::Integer(10));
+        vs.set_global("B", Value::Integer(11));
+        vs.set_global("C", Value::Integer(12));
+        vs.set_global("D", Value::Integer(13));
+        vs.set_global("E", Value::Integer(14));
+        vs.set_global("F", Value::Integer(15));
+        vs.set_global("N", Value::Char('\n'));
+        vs.set_global("S", Value::Char(' '));
+        vs.set_global("X", Value::Integer(0));
+        vs.set_global("Y", Value::Integer(1));
+        vs.set_global("Z", Value::Integer(2));
         vs
     }
 }
 
-impl ops::Index<char> for Variables {
-    type Output = Value;
-    fn index(&self, name: char) -> &Self::Output {
-        &self.0[name as usize - b'A' as usize]
+impl Variables {
+    fn frame(&self) -> Frame {
+        *self
+            .frames
+            .last()
+            .expect("the program's frame is never left")
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        let word = self
+            .frames
+            .iter()
+            .rev()
+            .find(|f| f.word)
+            .expect("the program's frame is a word frame");
+        self.scopes[word.start..]
+            .iter()
+            .rev()
+            .chain(&self.scopes[..1])
+            .find_map(|scope| scope.get(name))
+    }
+
+    /// Sets `name` in the innermost scope of the current frame that has it, or makes it a new
+    /// variable in the innermost scope, shadowing any outer one.
+    pub fn set(&mut self, name: &str, v: Value) {
+        let start = self.frame().start;
+        match self.scopes[start..]
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(var) => *var = v,
+            None => {
+                self.scopes
+                    .last_mut()
+                    .expect("every frame has a scope")
+                    .insert(name.into(), v);
+            }
+        }
+    }
+
+    pub fn set_global(&mut self, name: &str, v: Value) {
+        self.scopes[0].insert(name.into(), v);
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn leave_scope(&mut self) {
+        debug_assert!(
+            self.scopes.len() > self.frame().start + 1,
+            "a frame's first scope is only left with the frame"
+        );
+        self.scopes.pop();
+    }
+
+    /// Starts the frame of a word call if `word`, or else of a block run for an element, with a
+    /// scope of its own.
+    pub fn enter_frame(&mut self, word: bool) {
+        self.frames.push(Frame {
+            start: self.scopes.len(),
+            word,
+        });
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn leave_frame(&mut self) {
+        debug_assert!(self.frames.len() > 1, "the program's frame is never left");
+        if let Some(frame) = self.frames.pop() {
+            self.scopes.truncate(frame.start);
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scopes
+            .iter()
+            .flat_map(HashMap::keys)
+            .map(String::as_str)
     }
 }
 
-impl ops::IndexMut<char> for Variables {
-    fn index_mut(&mut self, name: char) -> &mut Self::Output {
-        &mut self.0[name as usize - b'A' as usize]
+/// How deeply blocks and calls to user-defined words can nest before giving up, unless changed with
+/// [`Stack::set_max_call_depth`]. Each level takes a few native stack frames, up to about 1.6KB in
+/// a release build and 4KB in a debug one, so this is low enough for runaway recursion to stop
+/// here rather than overflow the 8MB stack of a main thread, in a release build.
+pub const MAX_CALL_DEPTH: usize = 2500;
+
+/// The words defined with `: name { ... }`, by name.
+#[derive(Debug, Default)]
+pub struct Words(HashMap<String, Rc<[Rc<dyn Operator>]>>);
+
+impl Words {
+    pub fn get(&self, name: &str) -> Option<Rc<[Rc<dyn Operator>]>> {
+        self.0.get(name).cloned()
+    }
+
+    /// Defines `name` as `body`, replacing any previous definition.
+    pub fn define(&mut self, name: &str, body: Rc<[Rc<dyn Operator>]>) {
+        self.0.insert(name.into(), body);
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
     }
 }
 
@@ -43,6 +180,12 @@ pub struct Stack<'i> {
     pub indent: usize,
     io_input: &'i mut dyn BufRead,
     variables: Rc<RefCell<Variables>>,
+    words: Rc<RefCell<Words>>,
+    call_depth: Rc<Cell<usize>>,
+    max_call_depth: usize,
+    segmentation: Segmentation,
+    overflow: Overflow,
+    mode: Mode,
     s: Vec<Value>,
 }
 
@@ -64,21 +207,40 @@ impl<'i> Stack<'i> {
         indent: usize,
         io_input: &'i mut dyn BufRead,
         variables: Rc<RefCell<Variables>>,
+        words: Rc<RefCell<Words>>,
+        call_depth: Rc<Cell<usize>>,
     ) -> Self {
         println!("{}START[{}]", " ".repeat(indent), indent);
         Self {
             indent,
             io_input,
             variables,
+            words,
+            call_depth,
+            max_call_depth: MAX_CALL_DEPTH,
+            segmentation: Default::default(),
+            overflow: Default::default(),
+            mode: Default::default(),
             s: Default::default(),
         }
     }
 
     #[cfg(not(debug_assertions))]
-    fn new(io_input: &'i mut dyn BufRead, variables: Rc<RefCell<Variables>>) -> Self {
+    fn new(
+        io_input: &'i mut dyn BufRead,
+        variables: Rc<RefCell<Variables>>,
+        words: Rc<RefCell<Words>>,
+        call_depth: Rc<Cell<usize>>,
+    ) -> Self {
         Self {
             io_input,
             variables,
+            words,
+            call_depth,
+            max_call_depth: MAX_CALL_DEPTH,
+            segmentation: Default::default(),
+            overflow: Default::default(),
+            mode: Default::default(),
             s: Default::default(),
         }
     }
@@ -89,18 +251,62 @@ impl<'i> Stack<'i> {
             0,
             io_input,
             Default::default(),
+            Default::default(),
+            Default::default(),
         )
     }
 
     pub fn sub_stack(&mut self) -> Stack<'_> {
         #[cfg(debug_assertions)]
         let indent = self.indent + 1;
-        Stack::new(
+        let (segmentation, overflow, mode) = (self.segmentation, self.overflow, self.mode);
+        let max_call_depth = self.max_call_depth;
+        let mut sub_stack = Stack::new(
             #[cfg(debug_assertions)]
             indent,
             &mut self.io_input,
             self.variables.clone(),
-        )
+            self.words.clone(),
+            self.call_depth.clone(),
+        );
+        sub_stack.segmentation = segmentation;
+        sub_stack.overflow = overflow;
+        sub_stack.mode = mode;
+        sub_stack.max_call_depth = max_call_depth;
+        sub_stack
+    }
+
+    /// How string operators split strings into characters.
+    pub fn segmentation(&self) -> Segmentation {
+        self.segmentation
+    }
+
+    pub fn set_segmentation(&mut self, segmentation: Segmentation) {
+        self.segmentation = segmentation;
+    }
+
+    /// What arithmetic does with integers that don't fit in an `i64`.
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// How deeply blocks and word calls can nest, [`MAX_CALL_DEPTH`] unless changed. A deeper
+    /// limit needs a thread with a bigger native stack.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// How `eval` splits code into tokens.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
     }
 
     pub fn push(&mut self, v: Value) {
@@ -149,6 +355,11 @@ impl<'i> Stack<'i> {
         std::mem::take(&mut self.s)
     }
 
+    /// Replaces everything on the stack with `s`.
+    pub fn restore(&mut self, s: Vec<Value>) {
+        self.s = s;
+    }
+
     pub fn as_slice(&self) -> &[Value] {
         &self.s
     }
@@ -165,14 +376,80 @@ impl<'i> Stack<'i> {
         }
     }
 
-    pub fn push_var(&mut self, var: char) {
-        self.s.push(self.variables.borrow()[var].clone());
+    pub fn push_var(&mut self, var: &str) -> RuntimeResult<()> {
+        let v = self.variables.borrow().get(var).cloned();
+        self.s
+            .push(v.ok_or_else(|| RuntimeError::UndefinedVariable(var.into()))?);
+        Ok(())
+    }
+
+    /// Copies the top of the stack to `var`, in the innermost scope that has it, see
+    /// [`Variables::set`].
+    pub fn pop_var(&mut self, var: &str) -> RuntimeResult<()> {
+        let v = self.top().cloned()?;
+        self.variables.borrow_mut().set(var, v);
+        Ok(())
     }
 
-    pub fn pop_var(&mut self, var: char) -> RuntimeResult<()> {
-        self.variables.borrow_mut()[var] = self.top().map(Clone::clone)?;
+    /// Copies the top of the stack to the global `var`.
+    pub fn pop_global_var(&mut self, var: &str) -> RuntimeResult<()> {
+        let v = self.top().cloned()?;
+        self.variables.borrow_mut().set_global(var, v);
         Ok(())
     }
+
+    pub fn enter_scope(&mut self) {
+        self.variables.borrow_mut().enter_scope();
+    }
+
+    pub fn leave_scope(&mut self) {
+        self.variables.borrow_mut().leave_scope();
+    }
+
+    /// Starts the frame of a word call, see [`Variables`].
+    pub fn enter_frame(&mut self) {
+        self.variables.borrow_mut().enter_frame(true);
+    }
+
+    /// Starts the frame of a block run for an element, see [`Variables`].
+    pub fn enter_block_frame(&mut self) {
+        self.variables.borrow_mut().enter_frame(false);
+    }
+
+    pub fn leave_frame(&mut self) {
+        self.variables.borrow_mut().leave_frame();
+    }
+
+    pub fn word(&self, name: &str) -> Option<Rc<[Rc<dyn Operator>]>> {
+        self.words.borrow().get(name)
+    }
+
+    pub fn define_word(&mut self, name: &str, body: Rc<[Rc<dyn Operator>]>) {
+        self.words.borrow_mut().define(name, body);
+    }
+
+    /// The words and variables defined so far.
+    pub fn names(&self) -> Names {
+        Names {
+            words: self.words.borrow().names().map(String::from).collect(),
+            variables: self.variables.borrow().names().map(String::from).collect(),
+        }
+    }
+
+    /// Counts one more nested block or word call, failing if there are already as many as the
+    /// limit.
+    pub fn enter_call(&mut self) -> RuntimeResult<()> {
+        let depth = self.call_depth.get();
+        if depth >= self.max_call_depth {
+            return Err(RuntimeError::RecursionMax(self.max_call_depth));
+        }
+        self.call_depth.set(depth + 1);
+        Ok(())
+    }
+
+    pub fn leave_call(&mut self) {
+        self.call_depth.set(self.call_depth.get() - 1);
+    }
 }
 
 impl fmt::Display for Stack<'_> {
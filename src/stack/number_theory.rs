@@ -0,0 +1,217 @@
+use super::Value;
+use crate::{error::runtime::*, util::overflow::Overflow};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+/// The bases used by the Miller-Rabin test, which make it exact for every number below
+/// 3.3 * 10^24 and all but certain for bigger ones.
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Factors below this are found by trial division, the rest with Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u32 = 1000;
+
+/// How many steps of Pollard's rho `factor` takes, over all its tries, before giving up. This is
+/// enough for factors of up to about nine digits.
+const MAX_RHO_STEPS: usize = 1 << 16;
+
+/// How many steps of Pollard's rho are taken between each gcd.
+const RHO_BATCH: usize = 64;
+
+impl Value {
+    /// The value of an integer as a big integer.
+    fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Value::Integer(i) => Some(BigInt::from(*i)),
+            Value::BigInt(b) => Some(b.clone()),
+            _ => None,
+        }
+    }
+
+    /// Applies `f` to two integers, limiting the result with `overflow`.
+    fn integer_op(
+        self,
+        other: Value,
+        op: &'static str,
+        overflow: Overflow,
+        f: fn(&BigInt, &BigInt) -> BigInt,
+    ) -> RuntimeResult<Value> {
+        match (self.as_bigint(), other.as_bigint()) {
+            (Some(a), Some(b)) => overflow
+                .integer(f(&a, &b))
+                .ok_or(RuntimeError::InvalidOperation(vec![self, other], op)),
+            _ => Err(RuntimeError::InvalidOperation(vec![self, other], op)),
+        }
+    }
+
+    /// The greatest common divisor, which is never negative.
+    pub fn gcd_with(self, other: Value, overflow: Overflow) -> RuntimeResult<Value> {
+        self.integer_op(other, "gcd", overflow, BigInt::gcd)
+    }
+
+    /// The least common multiple, which is never negative.
+    pub fn lcm_with(self, other: Value, overflow: Overflow) -> RuntimeResult<Value> {
+        self.integer_op(other, "lcm", overflow, BigInt::lcm)
+    }
+
+    /// Whether an integer is prime, as `1` or `0`.
+    pub fn prime(self) -> RuntimeResult<Value> {
+        match self.as_bigint() {
+            Some(n) => Ok(is_prime(&n).into()),
+            None => crate::rt_error!(op: self => [prime]),
+        }
+    }
+
+    /// The prime factors of a positive integer, from smallest to biggest and repeated as many
+    /// times as they divide it.
+    pub fn factor(self) -> RuntimeResult<Value> {
+        let Some(mut n) = self.as_bigint() else {
+            crate::rt_error!(op: self => [factor]);
+        };
+        if !n.is_positive() {
+            return Err(RuntimeError::Domain(vec![self], "factor"));
+        }
+        let mut factors = Vec::new();
+        for p in (2..TRIAL_DIVISION_LIMIT).map(BigInt::from) {
+            if &p * &p > n {
+                break;
+            }
+            while n.is_multiple_of(&p) {
+                n /= &p;
+                factors.push(p.clone());
+            }
+        }
+        let mut steps = MAX_RHO_STEPS;
+        if factor_into(n, &mut factors, &mut steps).is_none() {
+            return Err(RuntimeError::InvalidOperation(
+                vec![self],
+                "factor_too_large",
+            ));
+        }
+        factors.sort();
+        Ok(Value::Array(factors.into_iter().map(Value::from).collect()))
+    }
+
+    /// Converts between a non negative integer and the array of its digits in `base`, most
+    /// significant first.
+    pub fn base_with(self, base: Value, overflow: Overflow) -> RuntimeResult<Value> {
+        let radix = match base.as_bigint() {
+            Some(b) if b > BigInt::one() => b,
+            Some(_) => return Err(RuntimeError::Domain(vec![self, base], "base")),
+            None => crate::rt_error!(op: self, base => [base]),
+        };
+        match &self {
+            Value::Array(digits) => {
+                let mut n = BigInt::zero();
+                for d in digits.iter() {
+                    let d = match d.as_bigint() {
+                        Some(d) if !d.is_negative() && d < radix => d,
+                        Some(_) => return Err(RuntimeError::Domain(vec![self, base], "base")),
+                        None => crate::rt_error!(op: self, base => [base]),
+                    };
+                    n = n * &radix + d;
+                }
+                overflow
+                    .integer(n)
+                    .ok_or(RuntimeError::InvalidOperation(vec![self, base], "base"))
+            }
+            Value::Integer(_) | Value::BigInt(_) => {
+                let mut n = self.as_bigint().unwrap_or_default();
+                if n.is_negative() {
+                    return Err(RuntimeError::Domain(vec![self, base], "base"));
+                }
+                let mut digits = Vec::new();
+                loop {
+                    let (q, r) = n.div_rem(&radix);
+                    digits.push(Value::from(r));
+                    if q.is_zero() {
+                        break;
+                    }
+                    n = q;
+                }
+                digits.reverse();
+                Ok(Value::Array(digits.into()))
+            }
+            _ => crate::rt_error!(op: self, base => [base]),
+        }
+    }
+
+    /// An integer written in `radix` with lowercase digits and no prefix, like `-ff`.
+    pub fn to_radix(self, radix: u32, op: &'static str) -> RuntimeResult<Value> {
+        match self.as_bigint() {
+            Some(n) => Ok(Value::from(n.to_str_radix(radix))),
+            None => Err(RuntimeError::InvalidOperation(vec![self], op)),
+        }
+    }
+}
+
+/// The Miller-Rabin primality test with [`WITNESSES`] as bases.
+fn is_prime(n: &BigInt) -> bool {
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for p in WITNESSES.map(BigInt::from) {
+        if n == &p {
+            return true;
+        }
+        if n.is_multiple_of(&p) {
+            return false;
+        }
+    }
+    let n_1 = n - BigInt::one();
+    let s = n_1.trailing_zeros().unwrap_or_default();
+    let d = &n_1 >> s;
+    'witness: for a in WITNESSES.map(BigInt::from) {
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Pushes the prime factors of `n`, which has none below [`TRIAL_DIVISION_LIMIT`], to `factors`,
+/// or gives `None` if that takes more than `steps` steps of Pollard's rho.
+fn factor_into(n: BigInt, factors: &mut Vec<BigInt>, steps: &mut usize) -> Option<()> {
+    if n.is_one() {
+        return Some(());
+    }
+    if is_prime(&n) {
+        factors.push(n);
+        return Some(());
+    }
+    let d = divisor(&n, steps)?;
+    factor_into(&n / &d, factors, steps)?;
+    factor_into(d, factors, steps)
+}
+
+/// A non trivial divisor of the odd composite `n`, found with Pollard's rho, or `None` if that
+/// takes more than `steps` steps.
+fn divisor(n: &BigInt, steps: &mut usize) -> Option<BigInt> {
+    let mut c = BigInt::one();
+    loop {
+        let f = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y, mut d) = (BigInt::from(2), BigInt::from(2), BigInt::one());
+        while d.is_one() {
+            let mut q = BigInt::one();
+            for _ in 0..RHO_BATCH {
+                *steps = steps.checked_sub(1)?;
+                x = f(&x);
+                y = f(&f(&y));
+                q = q * (&x - &y).abs() % n;
+            }
+            d = q.gcd(n);
+        }
+        if &d != n {
+            return Some(d);
+        }
+        c += 1;
+    }
+}
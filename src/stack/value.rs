@@ -1,22 +1,35 @@
-use crate::{error::both::*, ops::Operator, run_on, util::str_ext::StrExt};
+use super::{Map, Shared};
+use crate::util::{overflow::Overflow, sets, text::Segmentation};
+use crate::{error::both::*, ops::Operator};
 use itertools::Itertools;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
 use std::{
     cmp::{self, Ordering},
     convert::TryInto,
     fmt::{self, Write},
+    hash::{Hash, Hasher},
     ops,
     rc::Rc,
+    str::FromStr,
 };
 
-use super::Stack;
-
 #[derive(Clone, Debug)]
 pub enum Value {
     Char(char),
     Integer(i64),
+    /// An integer that doesn't fit in an `i64`. Arithmetic on `Integer`s is promoted to this on
+    /// overflow, and any result that fits in an `i64` again is turned back into an `Integer`.
+    BigInt(BigInt),
+    /// An exact fraction, made by `e/` or literals like `3/4`. It's never a whole number, those
+    /// are turned back into integers.
+    Rational(BigRational),
     Float(f64),
-    Str(String), // TODO: try to make it a cow
-    Array(Vec<Value>),
+    Str(Shared<String>),
+    Array(Shared<Vec<Value>>),
+    /// A map literal, `#[ key value ... ]`.
+    Map(Map),
     Block(Vec<Rc<dyn Operator>>),
 }
 
@@ -28,39 +41,77 @@ impl Default for Value {
 
 impl cmp::PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        self.partial_cmp(other) == Some(Ordering::Equal)
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl cmp::PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for Value {}
+
+/// Values are ordered first by kind: numbers, then chars, strings, arrays, maps and blocks.
+///
+/// Numbers of any variant are compared by their exact value, so `1`, `1.0` and `2/2` are equal
+/// and `0.1` is a little more than `1/10`. Negative infinity is the smallest number, then the
+/// finite ones, then infinity and `NaN` is the largest (and equal to itself). Strings, arrays,
+/// maps and blocks are compared lexicographically, blocks by the text of their operators.
+impl cmp::Ord for Value {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
         match (self, other) {
-            (Value::Char(c0), Value::Char(c1)) => c0.partial_cmp(c1),
-            (Value::Integer(i0), Value::Integer(i1)) => i0.partial_cmp(i1),
-            (Value::Float(f0), Value::Integer(i1)) => f0.partial_cmp(&(*i1 as f64)),
-            (Value::Integer(i0), Value::Float(f1)) => (*i0 as f64).partial_cmp(f1),
-            (Value::Float(f0), Value::Float(f1)) => f0.partial_cmp(f1),
-            (Value::Str(s0), Value::Str(s1)) => s0.partial_cmp(s1),
-            (Value::Array(a0), Value::Array(a1)) => a0.partial_cmp(a1),
+            (Value::Integer(i0), Value::Integer(i1)) => i0.cmp(i1),
+            (Value::Float(f0), Value::Float(f1)) => f0
+                .partial_cmp(f1)
+                .unwrap_or_else(|| f0.is_nan().cmp(&f1.is_nan())),
+            (Value::Char(c0), Value::Char(c1)) => c0.cmp(c1),
+            (Value::Str(s0), Value::Str(s1)) => s0.cmp(s1),
+            (Value::Array(a0), Value::Array(a1)) => a0.cmp(a1),
+            (Value::Map(m0), Value::Map(m1)) => m0.cmp(m1),
             (Value::Block(b0), Value::Block(b1)) => b0
                 .iter()
                 .map(|o| o.as_str())
-                .zip(b1.iter().map(|o| o.as_str()))
-                .all(|(b0, b1)| b0 == b1)
-                .then(|| Ordering::Equal),
-            _ => None,
+                .cmp(b1.iter().map(|o| o.as_str())),
+            _ => match (self.number(), other.number()) {
+                (Some(n0), Some(n1)) => n0.cmp(&n1),
+                _ => self.rank().cmp(&other.rank()),
+            },
         }
     }
 }
 
-impl Eq for Value {}
-
-impl cmp::Ord for Value {
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Value::Integer(i) => i.hash(state),
+            Value::Float(f)
+                if f.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(f) =>
+            {
+                (*f as i64).hash(state)
+            }
+            Value::BigInt(_) | Value::Rational(_) | Value::Float(_) => self.number().hash(state),
+            Value::Char(c) => c.hash(state),
+            Value::Str(s) => s.hash(state),
+            Value::Array(a) => a.hash(state),
+            Value::Map(m) => m.hash(state),
+            Value::Block(b) => b.iter().for_each(|o| o.as_str().hash(state)),
+        }
     }
 }
 
+/// A number's exact value, to compare numbers of different variants. The variants are declared
+/// in the order they sort in.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Number {
+    NegInfinity,
+    Finite(BigRational),
+    Infinity,
+    NaN,
+}
+
 impl From<&Value> for bool {
     fn from(v: &Value) -> Self {
         match v {
@@ -68,6 +119,7 @@ impl From<&Value> for bool {
             Value::Float(f) if *f == 0.0 => false,
             Value::Str(a) if a.is_empty() => false,
             Value::Array(v) if v.is_empty() => false,
+            Value::Map(m) if m.is_empty() => false,
             _ => true,
         }
     }
@@ -77,9 +129,10 @@ impl From<Value> for bool {
     fn from(v: Value) -> Self {
         match v {
             Value::Char('\0') | Value::Integer(0) => false,
-            Value::Float(f) if f == 0.0 => false,
+            Value::Float(0.0) => false,
             Value::Str(a) if a.is_empty() => false,
             Value::Array(v) if v.is_empty() => false,
+            Value::Map(m) if m.is_empty() => false,
             _ => true,
         }
     }
@@ -101,7 +154,78 @@ impl_from_rust! {
     Integer [usize, i64, u32, i32, u16, u8, bool];
     Float [f32, f64];
     Char [char];
-    Str [String];
+    Map [Map];
+}
+
+impl From<BigInt> for Value {
+    fn from(b: BigInt) -> Self {
+        match b.to_i64() {
+            Some(i) => Self::Integer(i),
+            None => Self::BigInt(b),
+        }
+    }
+}
+
+impl From<i128> for Value {
+    fn from(i: i128) -> Self {
+        Self::from(BigInt::from(i))
+    }
+}
+
+impl From<u64> for Value {
+    fn from(i: u64) -> Self {
+        Self::from(BigInt::from(i))
+    }
+}
+
+impl From<BigRational> for Value {
+    fn from(r: BigRational) -> Self {
+        if r.is_integer() {
+            Self::from(r.to_integer())
+        } else {
+            Self::Rational(r)
+        }
+    }
+}
+
+fn big_to_f64(b: &BigInt) -> f64 {
+    b.to_f64().unwrap_or(f64::NAN)
+}
+
+/// The most elements (or bytes, for strings) that repeating an array or string with `*` can make.
+pub const MAX_REPEAT_LEN: usize = 1 << 24;
+
+/// The length of `len` elements repeated `times` times, if it is a valid repetition and fits in
+/// [`MAX_REPEAT_LEN`].
+fn repeat_len(len: usize, times: i64) -> Option<usize> {
+    usize::try_from(times)
+        .ok()
+        .and_then(|times| len.checked_mul(times))
+        .filter(|len| *len <= MAX_REPEAT_LEN)
+}
+
+/// The most bits that raising an integer or rational to a power with `#` can make, counting both
+/// the numerator and the denominator of rationals.
+pub const MAX_POW_BITS: u64 = 1 << 20;
+
+/// About how many bits `b` takes.
+fn log2(b: &BigInt) -> f64 {
+    match b.abs().to_f64() {
+        Some(f) if f.is_finite() => f.log2(),
+        _ => b.bits() as f64,
+    }
+}
+
+/// Moves `c` by `by` code points, if that lands on a valid `char`.
+fn shift_char(c: char, by: i64) -> Option<char> {
+    (c as i64)
+        .checked_add(by)
+        .and_then(|c| u32::try_from(c).ok())
+        .and_then(char::from_u32)
+}
+
+fn ratio_to_f64(r: &BigRational) -> f64 {
+    r.to_f64().unwrap_or(f64::NAN)
 }
 
 impl<T> From<Vec<T>> for Value
@@ -122,6 +246,24 @@ where
     }
 }
 
+impl From<Shared<String>> for Value {
+    fn from(s: Shared<String>) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<Shared<Vec<Value>>> for Value {
+    fn from(a: Shared<Vec<Value>>) -> Self {
+        Self::Array(a)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::Str(s.into())
+    }
+}
+
 impl From<&str> for Value {
     fn from(s: &str) -> Self {
         Self::from(s.to_string())
@@ -129,12 +271,44 @@ impl From<&str> for Value {
 }
 
 impl Value {
-    pub fn and(self, other: Value) -> Value {
-        bool::from(&self).then(|| other).unwrap_or(self)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Char(_) => "char",
+            Value::Integer(_) | Value::BigInt(_) => "integer",
+            Value::Rational(_) => "rational",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+            Value::Block(_) => "block",
+        }
     }
 
-    pub fn or(self, other: Value) -> Value {
-        bool::from(&self).then(|| self).unwrap_or(other)
+    /// Where the value's kind goes in the order between values of different kinds.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Integer(_) | Value::BigInt(_) | Value::Rational(_) | Value::Float(_) => 0,
+            Value::Char(_) => 1,
+            Value::Str(_) => 2,
+            Value::Array(_) => 3,
+            Value::Map(_) => 4,
+            Value::Block(_) => 5,
+        }
+    }
+
+    fn number(&self) -> Option<Number> {
+        Some(match self {
+            Value::Float(f) if f.is_nan() => Number::NaN,
+            Value::Float(f) if *f == f64::INFINITY => Number::Infinity,
+            Value::Float(f) if *f == f64::NEG_INFINITY => Number::NegInfinity,
+            Value::Float(f) => Number::Finite(BigRational::from_float(*f)?),
+            _ => Number::Finite(self.to_ratio()?),
+        })
+    }
+
+    /// Displays the value the way it would be written in a program.
+    pub fn literal(&self) -> Literal<'_> {
+        Literal(self)
     }
 
     pub fn min(self, other: Value) -> RuntimeResult<Value> {
@@ -145,26 +319,82 @@ impl Value {
         Ok(if other < self { self } else { other })
     }
 
-    pub fn pow(self, other: Value) -> RuntimeResult<Self> {
+    /// The value as an exact fraction, if it is an integer or a rational.
+    fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            Value::Integer(i) => Some(BigInt::from(*i).into()),
+            Value::BigInt(b) => Some(b.clone().into()),
+            Value::Rational(r) => Some(r.clone()),
+            _ => None,
+        }
+    }
+
+    /// Division that never truncates: integers and rationals give a rational result.
+    pub fn exact_div_with(self, other: Value, overflow: Overflow) -> RuntimeResult<Self> {
+        match (self.to_ratio(), other.to_ratio()) {
+            (Some(_), Some(r2)) if r2.is_zero() => crate::rt_error!(op: self, other => [exact_div]),
+            (Some(r1), Some(r2)) => match overflow.limit((r1 / r2).into()) {
+                Some(v) => Ok(v),
+                None => crate::rt_error!(op: self, other => [exact_div]),
+            },
+            _ => match self.div_with(other, overflow) {
+                Err(RuntimeError::InvalidOperation(operands, _)) => {
+                    Err(RuntimeError::InvalidOperation(operands, "exact_div"))
+                }
+                r => r,
+            },
+        }
+    }
+
+    pub fn pow_with(self, other: Value, overflow: Overflow) -> RuntimeResult<Self> {
         match (&self, &other) {
-            (Self::Integer(i1), Self::Integer(i2)) => match (*i2).try_into() {
-                Ok(i2) => Ok(Self::Integer(i1.pow(i2))),
-                Err(_) if *i2 < 0 => {
-                    crate::rt_error!(op: self, other => [pow_with_negative_number])
+            (Self::Integer(_) | Self::BigInt(_), Self::Integer(i2)) => {
+                let exp = match u32::try_from(*i2) {
+                    Ok(exp) => exp,
+                    Err(_) if *i2 < 0 => {
+                        crate::rt_error!(op: self, other => [pow_with_negative_number])
+                    }
+                    Err(_) => crate::rt_error!(op: self, other => [pow_too_large]),
+                };
+                let base = match &self {
+                    Self::Integer(i1) => match i1.checked_pow(exp) {
+                        Some(p) => return Ok(Self::Integer(p)),
+                        None => BigInt::from(*i1),
+                    },
+                    Self::BigInt(b) => b.clone(),
+                    _ => unreachable!(),
+                };
+                if f64::from(exp) * log2(&base) > MAX_POW_BITS as f64 {
+                    return match overflow.huge_pow(&base, exp) {
+                        Some(v) => Ok(v),
+                        None if overflow == Overflow::Error => {
+                            crate::rt_error!(op: self, other => [pow])
+                        }
+                        None => crate::rt_error!(op: self, other => [pow_too_large]),
+                    };
                 }
-                Err(_) if *i2 > u32::MAX as i64 => {
-                    crate::rt_error!(op: self, other => [pow_too_large])
+                match overflow.integer(base.pow(exp)) {
+                    Some(v) => Ok(v),
+                    None => crate::rt_error!(op: self, other => [pow]),
                 }
-                Err(_) => unreachable!(),
-            },
-            (Self::Str(haystack), Self::Str(needle)) => {
-                Ok(haystack.find(needle).map(|i| i as i64).unwrap_or(-1).into())
             }
-            (Self::Str(haystack), Self::Char(needle)) => Ok(haystack
-                .find(*needle)
-                .map(|i| i as i64)
-                .unwrap_or(-1)
-                .into()),
+            (Self::Rational(r), Self::Integer(i2)) => match i32::try_from(*i2) {
+                Ok(exp)
+                    if f64::from(exp).abs() * (log2(r.numer()) + log2(r.denom()))
+                        <= MAX_POW_BITS as f64 =>
+                {
+                    match overflow.limit(r.pow(exp).into()) {
+                        Some(v) => Ok(v),
+                        None => crate::rt_error!(op: self, other => [pow]),
+                    }
+                }
+                _ => crate::rt_error!(op: self, other => [pow_too_large]),
+            },
+            (
+                Self::Integer(_) | Self::BigInt(_) | Self::Rational(_) | Self::Float(_),
+                Self::Rational(_) | Self::Float(_),
+            )
+            | (Self::Float(_), Self::Integer(_) | Self::BigInt(_)) => self.powf(other),
             _ => crate::rt_error!(op: self, other => [pow_substr]),
         }
     }
@@ -176,8 +406,11 @@ impl Value {
                 Ok(Ok(c)) => c,
                 _ => crate::rt_error!(convert: self, char),
             },
-            Value::Float(f) if (0.0..i8::MAX as f64).contains(&f) && f.fract() == 0.0 => {
-                f as u8 as _
+            Value::Float(f) if (0.0..=u32::MAX as f64).contains(&f) && f.fract() == 0.0 => {
+                match char::from_u32(f as u32) {
+                    Some(c) => c,
+                    None => crate::rt_error!(convert: self, char),
+                }
             }
             Value::Str(s) if s.chars().count() == 1 => s.chars().next().unwrap(),
             _ => crate::rt_error!(convert: self, char),
@@ -186,11 +419,10 @@ impl Value {
 
     pub fn to_float(self) -> RuntimeResult<Self> {
         Ok(Value::Float(match self {
-            Value::Char(c) => match c.is_ascii().then(|| c as u8) {
-                Some(c) => c as _,
-                None => crate::rt_error!(convert: self, f64),
-            },
+            Value::Char(c) => u32::from(c).into(),
             Value::Integer(i) => i as f64,
+            Value::BigInt(ref b) => big_to_f64(b),
+            Value::Rational(ref r) => ratio_to_f64(r),
             Value::Float(f) => f,
             Value::Str(ref s) => match s.parse() {
                 Ok(f) => f,
@@ -201,32 +433,78 @@ impl Value {
     }
 
     pub fn to_int(self) -> RuntimeResult<Self> {
-        Ok(Value::Integer(match self {
-            Value::Char(c) => c as i64,
-            Value::Integer(i) => i,
-            Value::Float(f) => f as i64,
-            Value::Str(ref s) => match s.parse() {
-                Ok(i) => i,
+        Ok(match self {
+            Value::Char(c) => Value::Integer(c as i64),
+            Value::Integer(_) | Value::BigInt(_) => self,
+            Value::Rational(r) => r.to_integer().into(),
+            Value::Float(f) if (i64::MIN as f64..i64::MAX as f64).contains(&f) => {
+                Value::Integer(f as i64)
+            }
+            Value::Float(f) => match BigInt::from_f64(f) {
+                Some(b) => b.into(),
+                None => crate::rt_error!(convert: self, i64),
+            },
+            Value::Str(ref s) => match s.parse::<BigInt>() {
+                Ok(b) => b.into(),
                 Err(_) => crate::rt_error!(convert: self, i64),
             },
             _ => crate::rt_error!(convert: self, i64),
-        }))
+        })
+    }
+
+    /// Like [`Value::to_int`], but integers that don't fit in an `i64` are handled according to
+    /// `overflow`.
+    pub fn to_int_with(self, overflow: Overflow) -> RuntimeResult<Self> {
+        match overflow.limit(self.clone().to_int()?) {
+            Some(v) => Ok(v),
+            None => crate::rt_error!(convert: self, i64),
+        }
     }
 
     pub fn to_str(self) -> RuntimeResult<Self> {
-        Ok(Value::Str(match self {
+        Ok(Value::from(match self {
             Value::Char(c) => c.to_string(),
             Value::Integer(i) => i.to_string(),
+            Value::BigInt(b) => b.to_string(),
+            Value::Rational(r) => r.to_string(),
             Value::Float(f) => f.to_string(),
-            Value::Str(s) => s,
+            Value::Str(s) => return Ok(Value::Str(s)),
             Value::Array(a) => {
                 let mut s = String::new();
                 let _ = write!(s, "{:?}", a);
                 s
             }
-            Value::Block(_) => crate::rt_error!(convert: self, String),
+            Value::Map(m) => Value::Map(m).literal().to_string(),
+            Value::Block(b) => block_source(&b),
         }))
     }
+
+    /// Converts to a string like [`Value::to_str`], but gives the string itself.
+    pub fn into_string(self) -> RuntimeResult<String> {
+        match self.to_str()? {
+            Value::Str(s) => Ok(s.into_inner()),
+            _ => unreachable!("to_str always gives a string"),
+        }
+    }
+
+    /// The array without repeated elements, or the string without repeated characters as split
+    /// by `segmentation`, keeping the first of each.
+    pub fn unique_with(self, segmentation: Segmentation) -> RuntimeResult<Value> {
+        match self {
+            Value::Array(a) => Ok(Value::from(sets::unique(a))),
+            Value::Str(s) => Ok(Value::from(sets::unique(segmentation.split(&s)).concat())),
+            x => crate::rt_error!(op: x => [unique]),
+        }
+    }
+
+    /// The source code for this value, which `eval` turns back into it. For a block, that is the
+    /// code inside the braces, so evaluating it runs the block.
+    pub fn quote(self) -> Value {
+        match self {
+            Value::Block(b) => Value::from(block_source(&b)),
+            v => Value::from(v.literal().to_string()),
+        }
+    }
 }
 
 macro_rules! impl_math {
@@ -241,13 +519,51 @@ macro_rules! impl_math {
             type Output = RuntimeResult<Self>;
 
             fn $name(self, other: Self) -> Self::Output {
+                paste::paste! { self.[<$name _with>](other, Overflow::default()) }
+            }
+        }
+
+        paste::paste! {
+        impl Value {
+            /// Like the operator, but integer results that don't fit in an `i64` are handled
+            /// according to `overflow`.
+            pub fn [<$name _with>](self, other: Self, overflow: Overflow) -> RuntimeResult<Self> {
+                use $trait as _;
+                let big = |b1: BigInt, b2: BigInt| -> RuntimeResult<Self> {
+                    if b2.is_zero() && matches!(stringify!($name), "div" | "rem") {
+                        crate::rt_error!(op: b1, b2 => [$name])
+                    }
+                    match overflow.integer((&b1).$name(&b2)) {
+                        Some(v) => Ok(v),
+                        None => crate::rt_error!(op: b1, b2 => [$name]),
+                    }
+                };
+                let exact = |r1: BigRational, r2: BigRational| -> RuntimeResult<Self> {
+                    if r2.is_zero() && matches!(stringify!($name), "div" | "rem") {
+                        crate::rt_error!(op: r1, r2 => [$name])
+                    }
+                    match overflow.limit((&r1).$name(&r2).into()) {
+                        Some(v) => Ok(v),
+                        None => crate::rt_error!(op: r1, r2 => [$name]),
+                    }
+                };
                 let v = match (self, other) {
-                    (Self::Integer(i1), Self::Integer(i2)) => paste::paste! {
-                        match i1.[<checked_ $name>](i2).map(Self::Integer) {
-                            Some(x) => x,
-                            None => crate::rt_error!(op: Self::Integer(i1), Self::Integer(i2) => [$name])
-                        }
+                    (Self::Integer(i1), Self::Integer(i2)) => match i1.[<checked_ $name>](i2) {
+                        Some(x) => Self::Integer(x),
+                        None => big(i1.into(), i2.into())?,
                     },
+                    (Self::BigInt(b1), Self::BigInt(b2)) => big(b1, b2)?,
+                    (Self::BigInt(b1), Self::Integer(i2)) => big(b1, i2.into())?,
+                    (Self::Integer(i1), Self::BigInt(b2)) => big(i1.into(), b2)?,
+                    (Self::BigInt(b1), Self::Float(f1)) => Self::Float(big_to_f64(&b1).$name(f1)),
+                    (Self::Float(f1), Self::BigInt(b1)) => Self::Float(f1.$name(big_to_f64(&b1))),
+                    (Self::Rational(r1), Self::Float(f1)) => Self::Float(ratio_to_f64(&r1).$name(f1)),
+                    (Self::Float(f1), Self::Rational(r1)) => Self::Float(f1.$name(ratio_to_f64(&r1))),
+                    (Self::Rational(r1), Self::Rational(r2)) => exact(r1, r2)?,
+                    (Self::Rational(r1), Self::Integer(i2)) => exact(r1, BigInt::from(i2).into())?,
+                    (Self::Rational(r1), Self::BigInt(b2)) => exact(r1, b2.into())?,
+                    (Self::Integer(i1), Self::Rational(r2)) => exact(BigInt::from(i1).into(), r2)?,
+                    (Self::BigInt(b1), Self::Rational(r2)) => exact(b1.into(), r2)?,
                     (Self::Integer(i1), Self::Float(f1)) => Self::Float((i1 as f64).$name(f1)),
                     (Self::Float(f1), Self::Integer(i1)) => Self::Float(f1.$name(i1 as f64)),
                     (Self::Float(f1), Self::Float(f2)) => Self::Float(f1.$name(f2)),
@@ -257,21 +573,40 @@ macro_rules! impl_math {
                 Ok(v)
             }
         }
+        }
     }
 }
 
 impl_math!(ops::Add, add {
-    (Self::Char(c0), Self::Char(c1)) => Self::Char((c0 as u8 + c1 as u8) as char),
-    (Self::Char(c), Self::Integer(i)) => Self::Char((c as u8 + i as u8) as char),
-    (Self::Str(s1), Self::Str(s2)) => Self::Str(s1 + &s2),
-    (Self::Str(s), any) => Self::Str(
-        s + &if let Value::Str(s) = any.to_str()? { s } else { unreachable!() }
-    ),
+    (Self::Char(c0), Self::Char(c1)) => match shift_char(c0, c1 as i64) {
+        Some(c) => Self::Char(c),
+        None => crate::rt_error!(op: c0, c1 => [add]),
+    },
+    (Self::Char(c), Self::Integer(i)) => match shift_char(c, i) {
+        Some(c) => Self::Char(c),
+        None => crate::rt_error!(op: c, i => [add]),
+    },
+    (Self::Integer(i), Self::Char(c)) => match shift_char(c, i) {
+        Some(c) => Self::Char(c),
+        None => crate::rt_error!(op: i, c => [add]),
+    },
+    (Self::Str(mut s1), Self::Str(s2)) => {
+        s1.push_str(&s2);
+        Self::Str(s1)
+    },
+    (Self::Str(mut s), any) => {
+        s.push_str(&if let Value::Str(s) = any.to_str()? { s } else { unreachable!() });
+        Self::Str(s)
+    },
     (Self::Char(c), Self::Str(mut s)) => Self::Str({ s.push(c); s }),
     (Self::Array(mut a1), Self::Array(a2)) => {
         a1.extend(a2);
         Self::Array(a1)
     },
+    (Self::Map(mut m1), Self::Map(m2)) => {
+        m1.extend(m2);
+        Self::Map(m1)
+    },
     (Self::Array(mut a1), any) => {
         a1.push(any);
         Self::Array(a1)
@@ -282,22 +617,26 @@ impl_math!(ops::Add, add {
     },
 });
 impl_math!(ops::Sub, sub {
-    (Self::Char(c), Self::Integer(i)) => Self::Char((c as u8 - i as u8) as char),
+    (Self::Char(c), Self::Integer(i)) => match i.checked_neg().and_then(|i| shift_char(c, i)) {
+        Some(c) => Self::Char(c),
+        None => crate::rt_error!(op: c, i => [sub]),
+    },
+    (Self::Char(c0), Self::Char(c1)) => Self::Integer(c0 as i64 - c1 as i64),
+    (Self::Array(a1), Self::Array(a2)) => Self::from(sets::difference(a1, a2)),
 });
 impl_math!(ops::Mul, mul {
-    (Self::Array(mut a), Self::Integer(i)) => Self::Array({
-        let clone = a.clone();
-        for _ in 0..(i.saturating_sub(2)) {
-            a.extend(clone.clone());
-        }
-        a.extend(clone);
-        a
-    }),
-    (Self::Str(s), Self::Integer(i)) => Self::Str(s.repeat(i as usize)),
+    (Self::Array(a), Self::Integer(i)) => match repeat_len(a.len(), i) {
+        Some(len) => Value::from(a.iter().cycle().take(len).cloned().collect::<Vec<_>>()),
+        None => crate::rt_error!(op: a, i => [repeat]),
+    },
+    (Self::Str(s), Self::Integer(i)) => match repeat_len(s.len(), i) {
+        Some(_) => Value::from(s.repeat(i as usize)),
+        None => crate::rt_error!(op: s, i => [repeat]),
+    },
 });
 impl_math!(ops::Div, div {
     (Self::Str(s), Self::Str(delim)) => Self::Array(
-        s.split(&delim).filter(|x| !x.is_empty()).map(Value::from).collect()
+        s.split(delim.as_str()).filter(|x| !x.is_empty()).map(Value::from).collect()
     ),
 });
 impl_math!(ops::Rem, rem);
@@ -316,6 +655,9 @@ macro_rules! impl_bit {
             fn $name(self, other: Self) -> Self::Output {
                 let v = match (self, other) {
                     (Self::Integer(i1), Self::Integer(i2)) => Self::Integer(i1.$name(i2)),
+                    (Self::BigInt(b1), Self::BigInt(b2)) => b1.$name(b2).into(),
+                    (Self::BigInt(b1), Self::Integer(i2)) => b1.$name(BigInt::from(i2)).into(),
+                    (Self::Integer(i1), Self::BigInt(b2)) => BigInt::from(i1).$name(b2).into(),
                     $($pattern => $do,)*
                     (a, b) => crate::rt_error!(op: a, b => [$name]),
                 };
@@ -325,68 +667,192 @@ macro_rules! impl_bit {
     }
 }
 
-impl_bit!(ops::BitAnd, bitand);
-impl_bit!(ops::BitOr, bitor);
-impl_bit!(ops::BitXor, bitxor);
+impl_bit!(ops::BitAnd, bitand {
+    (Self::Array(a1), Self::Array(a2)) => Self::from(sets::intersection(a1, a2)),
+});
+impl_bit!(ops::BitOr, bitor {
+    (Self::Array(a1), Self::Array(a2)) => Self::from(sets::union(a1, a2)),
+});
+impl_bit!(ops::BitXor, bitxor {
+    (Self::Array(a1), Self::Array(a2)) => Self::from(sets::symmetric_difference(a1, a2)),
+});
 
-impl Value {
-    pub fn from_str(s: &str, i: &mut Stack<'_>) -> Result<Self, crate::Error> {
-        let value = s
-            .parse()
+impl FromStr for Value {
+    type Err = SyntaxError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
             .map(Value::Integer)
             .ok()
+            .or_else(|| s.parse::<BigInt>().map(Value::from).ok())
             .or_else(|| s.parse().map(Value::Float).ok())
+            .map(Ok)
+            .or_else(|| Value::parse_rational(s))
+            .or_else(|| Value::parse_number(s))
+            .or_else(|| Value::parse_char(s))
             .or_else(|| Value::parse_string(s))
-            .or_else(|| Value::parse_block(s));
-        match value {
-            Some(v) => Ok(v),
-            None => Value::parse_array(s, i),
-        }
+            .unwrap_or_else(|| Err(s.into()))
     }
+}
 
-    fn parse_array(s: &str, stack: &mut Stack<'_>) -> Result<Self, crate::Error> {
-        if s.starts_with('[') && s.ends_with(']') {
-            let array = run_on(s.trim_matches(&['[', ']'][..]), stack.sub_stack())?;
-            Ok(Value::Array(array))
-        } else {
-            Err(crate::Error::Runtime(RuntimeError::InvalidValueString(
-                s.into(),
-            )))
+impl Value {
+    /// Parses the number literals that rust doesn't: `0x`, `0o` and `0b` prefixed integers and
+    /// numbers with `_` digit separators.
+    fn parse_number(s: &str) -> Option<Result<Self, SyntaxError>> {
+        let (sign, unsigned) = match s.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", s.strip_prefix('+').unwrap_or(s)),
+        };
+        if !unsigned.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
         }
-    }
-
-    fn parse_string(s: &str) -> Option<Self> {
-        if s.starts_with('"') && s.ends_with('"') {
-            Some(Value::Str(s.trim_matches('"').into()))
+        let (radix, digits) = match unsigned.get(..2) {
+            Some("0x" | "0X") => (16, &unsigned[2..]),
+            Some("0o" | "0O") => (8, &unsigned[2..]),
+            Some("0b" | "0B") => (2, &unsigned[2..]),
+            _ => (10, unsigned),
+        };
+        let digits = format!("{}{}", sign, digits.replace('_', ""));
+        let value = if radix == 10 {
+            digits
+                .parse::<BigInt>()
+                .map(Value::from)
+                .ok()
+                .or_else(|| digits.parse().map(Value::Float).ok())
+        } else if digits[sign.len()..].chars().all(|c| c.is_digit(radix)) {
+            BigInt::parse_bytes(digits.as_bytes(), radix).map(Value::from)
         } else {
             None
-        }
+        };
+        Some(value.ok_or_else(|| SyntaxError::InvalidNumber(s.into())))
     }
 
-    fn parse_block(s: &str) -> Option<Self> {
-        if s.starts_with('{') && s.ends_with('}') {
-            Some(Value::Block(
-                s.trim_matches(&['{', '}'][..])
-                    .split_tokens()
-                    .map(|t| t.parse::<Box<dyn Operator>>().map(Rc::from))
-                    .map(Result::ok)
-                    .collect::<Option<_>>()?,
-            ))
+    /// Parses fraction literals, like `3/4` or `-1/2`.
+    fn parse_rational(s: &str) -> Option<Result<Self, SyntaxError>> {
+        let (numer, denom) = s.split_once('/')?;
+        if !denom.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let numer = numer.parse::<BigInt>().ok()?;
+        let denom = denom.parse::<BigInt>().ok()?;
+        Some(if denom.is_zero() {
+            Err(SyntaxError::InvalidNumber(s.into()))
         } else {
-            None
+            Ok(BigRational::new(numer, denom).into())
+        })
+    }
+
+    fn parse_char(s: &str) -> Option<Result<Self, SyntaxError>> {
+        let body = s.strip_prefix('\'')?.strip_suffix('\'')?;
+        Some(unescape(body).and_then(|c| {
+            let mut chars = c.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Value::Char(c)),
+                _ => Err(SyntaxError::InvalidChar(s.into())),
+            }
+        }))
+    }
+
+    fn parse_string(s: &str) -> Option<Result<Self, SyntaxError>> {
+        if let Some(raw) = s.strip_prefix('r') {
+            let hashes = &raw[..(raw.len() - raw.trim_start_matches('#').len())];
+            return raw
+                .strip_prefix(hashes)
+                .and_then(|r| r.strip_suffix(hashes))
+                .and_then(|r| r.strip_prefix('"'))
+                .and_then(|r| r.strip_suffix('"'))
+                .map(|r| Ok(Value::from(r)));
         }
+        s.strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .map(|s| unescape(s).map(Value::from))
     }
 }
 
+/// Resolves the escape sequences in the body of a string or char literal: `\n`, `\t`, `\r`,
+/// `\0`, `\\`, `\"`, `\'` and `\u{...}`.
+fn unescape(s: &str) -> Result<String, SyntaxError> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('\\') {
+        unescaped.push_str(&rest[..i]);
+        let escape = &rest[i..];
+        let invalid = |len: usize| SyntaxError::InvalidEscape(escape[..len].into());
+        let (c, len) = match escape[1..].chars().next() {
+            Some('n') => ('\n', 2),
+            Some('t') => ('\t', 2),
+            Some('r') => ('\r', 2),
+            Some('0') => ('\0', 2),
+            Some(c @ ('\\' | '"' | '\'')) => (c, 2),
+            Some('u') => match escape[2..]
+                .strip_prefix('{')
+                .and_then(|r| r.split_once('}'))
+            {
+                Some((code, _)) => {
+                    let len = code.len() + 4;
+                    match u32::from_str_radix(code, 16).ok().and_then(char::from_u32) {
+                        Some(c) => (c, len),
+                        None => return Err(invalid(len)),
+                    }
+                }
+                None => return Err(invalid(2)),
+            },
+            Some(c) => return Err(invalid(1 + c.len_utf8())),
+            None => return Err(invalid(1)),
+        };
+        unescaped.push(c);
+        rest = &escape[len..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Char(c) => write!(f, "c({:?})", c),
             Value::Integer(i) => write!(f, "i({})", i),
+            Value::BigInt(b) => write!(f, "i({})", b),
+            Value::Rational(r) => write!(f, "r({})", r),
             Value::Float(d) => write!(f, "f({})", d),
             Value::Str(s) => write!(f, "s({:?})", s),
             Value::Array(a) => write!(f, "a([{}])", a.iter().format(",")),
+            Value::Map(m) => write!(
+                f,
+                "m([{}])",
+                m.iter().map(|(k, v)| format!("{}:{}", k, v)).format(",")
+            ),
             Value::Block(b) => write!(f, "b([{}])", b.iter().format(",")),
         }
     }
 }
+
+fn block_source(b: &[Rc<dyn Operator>]) -> String {
+    b.iter().map(|o| o.as_str()).join(" ")
+}
+
+pub struct Literal<'v>(&'v Value);
+
+impl fmt::Display for Literal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Char(c) => write!(f, "{:?}", c),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::BigInt(b) => write!(f, "{}", b),
+            Value::Rational(r) => write!(f, "{}", r),
+            Value::Float(d) => write!(f, "{:?}", d),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Array(a) if a.is_empty() => f.write_str("[ ]"),
+            Value::Array(a) => write!(f, "[ {} ]", a.iter().map(Value::literal).format(" ")),
+            Value::Map(m) if m.is_empty() => f.write_str("#[ ]"),
+            Value::Map(m) => write!(
+                f,
+                "#[ {} ]",
+                m.iter()
+                    .map(|(k, v)| format!("{} {}", k.literal(), v.literal()))
+                    .format(" ")
+            ),
+            Value::Block(b) if b.is_empty() => f.write_str("{ }"),
+            Value::Block(b) => write!(f, "{{ {} }}", b.iter().map(|o| o.as_str()).format(" ")),
+        }
+    }
+}
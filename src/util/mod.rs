@@ -1,2 +1,5 @@
+pub mod overflow;
+pub mod sets;
 pub mod str_ext;
+pub mod text;
 //pub mod one_or_more;
@@ -0,0 +1,62 @@
+use crate::stack::Value;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive};
+
+/// What to do when the result of integer arithmetic doesn't fit in an `i64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Keep the exact result as a big integer.
+    #[default]
+    Promote,
+    /// Fail with a runtime error.
+    Error,
+    /// Wrap around, like C's unsigned arithmetic: `9223372036854775807 1 +` is the smallest
+    /// `i64`.
+    Wrapping,
+    /// Clamp to the smallest or largest `i64`.
+    Saturating,
+    /// Give the nearest float instead.
+    Float,
+}
+
+impl Overflow {
+    /// The value to use for the exact result `b` of some arithmetic, or `None` if it is an error.
+    pub fn integer(self, b: BigInt) -> Option<Value> {
+        if let Some(i) = b.to_i64() {
+            return Some(Value::Integer(i));
+        }
+        Some(match self {
+            Self::Promote => Value::BigInt(b),
+            Self::Error => return None,
+            Self::Wrapping => Value::Integer((b & BigInt::from(u64::MAX)).to_u64()? as i64),
+            Self::Saturating if b.is_negative() => Value::Integer(i64::MIN),
+            Self::Saturating => Value::Integer(i64::MAX),
+            Self::Float => Value::Float(b.to_f64()?),
+        })
+    }
+
+    /// The value to use for `base` to the power of `exp`, when that is too big to compute
+    /// exactly, or `None` if it is an error.
+    pub fn huge_pow(self, base: &BigInt, exp: u32) -> Option<Value> {
+        let negative = base.is_negative() && exp % 2 == 1;
+        Some(match self {
+            Self::Promote | Self::Error => return None,
+            Self::Wrapping => {
+                let modulus = BigInt::one() << 64;
+                let low = (base & BigInt::from(u64::MAX)).modpow(&BigInt::from(exp), &modulus);
+                Value::Integer(low.to_u64()? as i64)
+            }
+            Self::Saturating if negative => Value::Integer(i64::MIN),
+            Self::Saturating => Value::Integer(i64::MAX),
+            Self::Float => Value::Float(base.to_f64()?.powf(exp.into())),
+        })
+    }
+
+    /// Applies [`Overflow::integer`] to `v` if it is an integer.
+    pub fn limit(self, v: Value) -> Option<Value> {
+        match v {
+            Value::BigInt(b) => self.integer(b),
+            v => Some(v),
+        }
+    }
+}
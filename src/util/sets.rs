@@ -0,0 +1,58 @@
+use std::{collections::HashSet, hash::Hash};
+
+/// The elements of `items` without repeats, in the order they first appear. The other
+/// operations, except for [`difference`], give their elements in the same order.
+pub fn unique<T: Eq + Hash + Clone>(items: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter(|x| seen.insert(x.clone()))
+        .collect()
+}
+
+/// The elements in `a` or `b`.
+pub fn union<T: Eq + Hash + Clone>(
+    a: impl IntoIterator<Item = T>,
+    b: impl IntoIterator<Item = T>,
+) -> Vec<T> {
+    unique(a.into_iter().chain(b))
+}
+
+/// The elements in both `a` and `b`.
+pub fn intersection<T: Eq + Hash + Clone>(
+    a: impl IntoIterator<Item = T>,
+    b: impl IntoIterator<Item = T>,
+) -> Vec<T> {
+    let b = b.into_iter().collect::<HashSet<_>>();
+    unique(a.into_iter().filter(|x| b.contains(x)))
+}
+
+/// The elements in exactly one of `a` and `b`: those only in `a` first, then those only in `b`.
+pub fn symmetric_difference<T: Eq + Hash + Clone>(
+    a: impl IntoIterator<Item = T>,
+    b: impl IntoIterator<Item = T>,
+) -> Vec<T> {
+    let (a, b) = (
+        a.into_iter().collect::<Vec<_>>(),
+        b.into_iter().collect::<Vec<_>>(),
+    );
+    let (in_a, in_b) = (
+        a.iter().cloned().collect::<HashSet<_>>(),
+        b.iter().cloned().collect::<HashSet<_>>(),
+    );
+    unique(
+        a.into_iter()
+            .filter(|x| !in_b.contains(x))
+            .chain(b.into_iter().filter(|x| !in_a.contains(x))),
+    )
+}
+
+/// The elements of `a` that aren't in `b`. Unlike the other operations this keeps repeats, like
+/// GolfScript's `-`.
+pub fn difference<T: Eq + Hash>(
+    a: impl IntoIterator<Item = T>,
+    b: impl IntoIterator<Item = T>,
+) -> Vec<T> {
+    let b = b.into_iter().collect::<HashSet<_>>();
+    a.into_iter().filter(|x| !b.contains(x)).collect()
+}
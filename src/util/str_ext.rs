@@ -1,68 +1,346 @@
+use crate::{
+    error::{Error, SyntaxError},
+    ops,
+};
+use std::fmt;
+
 pub trait StrExt {
-    fn split_tokens(&self) -> Iter<'_>;
+    fn split_tokens(&self) -> Iter<'_> {
+        self.split_tokens_in(Mode::default())
+    }
+
+    fn split_tokens_in(&self, mode: Mode) -> Iter<'_>;
+}
+
+/// How source code is split into tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Tokens are separated by whitespace: `1 2 + { 3 * } %`.
+    #[default]
+    Spaced,
+    /// GolfScript style, tokens need no whitespace between them: `1 2+{3*}%`. Each token is the
+    /// longest number or operator that fits, so `_` always means dup (no digit separators) and a
+    /// `-` only starts a negative number at the beginning of a word. Comments still have to
+    /// start a word, there are no fraction literals, since `3/4` is `3 / 4`, and `#[` always
+    /// starts a map literal. The names of user-defined words and variables are split the same
+    /// way, so only single characters that aren't operators make usable names.
+    Golf,
+}
+
+/// Where a token is in the source: a byte range plus the (1-based) line and column of its first
+/// character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'s> {
+    pub text: &'s str,
+    pub span: Span,
+    pub mode: Mode,
+}
+
+impl<'s> Token<'s> {
+    /// The tokens between the delimiters of a block, array or map literal.
+    pub fn inner(&self) -> Iter<'s> {
+        let open = if self.text.starts_with("#[") { 2 } else { 1 };
+        Iter {
+            s: &self.text[open..(self.text.len() - 1)],
+            offset: self.span.start + open,
+            line: self.span.line,
+            column: self.span.column + open,
+            mode: self.mode,
+            at_word_start: true,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Iter<'a> {
     s: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+    mode: Mode,
+    at_word_start: bool,
+}
+
+impl<'a> Iter<'a> {
+    fn new(s: &'a str, mode: Mode) -> Self {
+        Self {
+            s,
+            offset: 0,
+            line: 1,
+            column: 1,
+            mode,
+            at_word_start: true,
+        }
+    }
+
+    fn span(&self, len: usize) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + len,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        for c in self.s[..len].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset += len;
+        self.s = &self.s[len..];
+    }
 }
 
-impl<'s> StrExt for &'s str {
-    fn split_tokens(&self) -> Iter<'s> {
-        Iter { s: self }
+impl StrExt for str {
+    fn split_tokens_in(&self, mode: Mode) -> Iter<'_> {
+        Iter::new(self, mode)
     }
 }
 
 impl StrExt for String {
-    fn split_tokens(&self) -> Iter<'_> {
-        Iter { s: self }
+    fn split_tokens_in(&self, mode: Mode) -> Iter<'_> {
+        Iter::new(self, mode)
     }
 }
 
 impl<'s> Iterator for Iter<'s> {
-    type Item = &'s str;
+    type Item = Result<Token<'s>, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.s = self.s.trim_start();
-        let fst = self.s.chars().next()?;
-        let end = match fst {
-            '"' => self.s[1..].find('"').map(|i| i + 2).unwrap_or(self.s.len()),
-            '[' => {
-                let mut i = 1;
-                self.s[1..]
-                    .find(|c: char| {
-                        match c {
-                            ']' if i == 1 => return true,
-                            ']' => i -= 1,
-                            '[' => i += 1,
-                            _ => (),
-                        }
-                        false
-                    })
-                    .map(|i| i + 2)
-                    .unwrap_or(self.s.len())
+        let end = loop {
+            let whitespace = self.s.len() - self.s.trim_start().len();
+            if whitespace > 0 {
+                self.advance(whitespace);
+                self.at_word_start = true;
             }
-            '{' => {
-                let mut i = 1;
-                self.s[1..]
-                    .find(|c: char| {
-                        match c {
-                            '}' if i == 1 => return true,
-                            '}' => i -= 1,
-                            '{' => i += 1,
-                            _ => (),
-                        }
-                        false
-                    })
-                    .map(|i| i + 2)
-                    .unwrap_or(self.s.len())
+            match comment_len(self.s).filter(|_| self.at_word_start) {
+                Some(Ok(len)) => self.advance(len),
+                Some(Err(e)) => break Err(e),
+                None => break token_len(self.s, self.mode, self.at_word_start)?,
             }
-            _ => self.s.find(char::is_whitespace).unwrap_or(self.s.len()),
         };
-        let r = &self.s[..end];
-        self.s = &self.s[end..];
-        Some(r)
+        match end {
+            Ok(end) => {
+                let token = Token {
+                    text: &self.s[..end],
+                    span: self.span(end),
+                    mode: self.mode,
+                };
+                self.advance(end);
+                self.at_word_start = false;
+                Some(Ok(token))
+            }
+            Err((e, at)) => {
+                self.advance(at);
+                let span = self.span(1);
+                self.s = "";
+                Some(Err(Error::from(e).at(span)))
+            }
+        }
+    }
+}
+
+/// Where a token failed to scan: the error and its byte offset from the start of the token.
+type ScanError = (SyntaxError, usize);
+
+/// The length of the token at the start of `s`, or `None` if there is nothing left.
+fn token_len(s: &str, mode: Mode, at_word_start: bool) -> Option<Result<usize, ScanError>> {
+    let word_len = |s| match mode {
+        Mode::Spaced => word_len(s),
+        Mode::Golf => golf_word_len(s, at_word_start),
+    };
+    Some(match s.chars().next()? {
+        '"' | '\'' => quoted_len(s),
+        'r' => raw_string_len(s).unwrap_or_else(|| Ok(word_len(s))),
+        '[' | '{' => delimited_len(s),
+        '#' if s[1..].starts_with('[') => delimited_len(&s[1..])
+            .map(|len| len + 1)
+            .map_err(|(e, at)| (e, at + 1)),
+        ']' => Err((SyntaxError::Unmatched("]"), 0)),
+        '}' => Err((SyntaxError::Unmatched("}"), 0)),
+        _ if at_word_start && s.starts_with("*)") => Err((SyntaxError::Unmatched("*)"), 0)),
+        _ => Ok(word_len(s)),
+    })
+}
+
+/// The length of the comment at the start of `s`, if there is one. Line comments start with
+/// `--` and go until the end of the line, block comments go from `(*` to `*)` and can be nested.
+fn comment_len(s: &str) -> Option<Result<usize, ScanError>> {
+    if s.starts_with("--") {
+        Some(Ok(s.find('\n').unwrap_or(s.len())))
+    } else if s.starts_with("(*") {
+        let mut depth = 0;
+        let mut i = 0;
+        while i < s.len() {
+            if s[i..].starts_with("(*") {
+                depth += 1;
+                i += 2;
+            } else if s[i..].starts_with("*)") {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(Ok(i));
+                }
+            } else {
+                i += s[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        Some(Err((SyntaxError::Unclosed("(*"), 0)))
+    } else {
+        None
+    }
+}
+
+fn word_len(s: &str) -> usize {
+    s.find(char::is_whitespace).unwrap_or(s.len())
+}
+
+/// The length of the longest number or operator at the start of `s`. If there is neither, the
+/// first character is taken on its own, to be reported as an unknown token by the parser.
+fn golf_word_len(s: &str, at_word_start: bool) -> usize {
+    if let Some(len) = golf_number_len(s, at_word_start) {
+        return len;
+    }
+    let word = &s[..s
+        .find(|c: char| c.is_whitespace() || "\"'[]{}".contains(c))
+        .unwrap_or(s.len())];
+    word.char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .rev()
+        .find(|len| ops::is_operator(&word[..*len]))
+        .or_else(|| s.chars().next().map(char::len_utf8))
+        .unwrap_or(s.len())
+}
+
+fn golf_number_len(s: &str, at_word_start: bool) -> Option<usize> {
+    let digits = |s: &str, radix| s.find(|c: char| !c.is_digit(radix)).unwrap_or(s.len());
+    let sign = usize::from(at_word_start && s.starts_with('-'));
+    let s = &s[sign..];
+    let mut len = Some(digits(s, 10)).filter(|len| *len > 0)?;
+    let radix = match s.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 && digits(&s[2..], radix) > 0 {
+        return Some(sign + 2 + digits(&s[2..], radix));
+    }
+    if let Some(fract) = s[len..].strip_prefix('.').map(|f| digits(f, 10)) {
+        if fract > 0 {
+            len += 1 + fract;
+        }
+    }
+    if let Some(exp) = s[len..].strip_prefix(['e', 'E']) {
+        let exp_sign = usize::from(exp.starts_with(['+', '-']));
+        let exp_digits = digits(&exp[exp_sign..], 10);
+        if exp_digits > 0 {
+            len += 1 + exp_sign + exp_digits;
+        }
+    }
+    Some(sign + len)
+}
+
+/// The length of the string or char literal at the start of `s`, skipping over escaped quotes.
+fn quoted_len(s: &str) -> Result<usize, ScanError> {
+    let (quote, unclosed) = if s.starts_with('"') {
+        ('"', "\"")
+    } else {
+        ('\'', "'")
+    };
+    let mut escaped = false;
+    s.char_indices()
+        .skip(1)
+        .find(|&(_, c)| {
+            match c {
+                '\\' if !escaped => escaped = true,
+                c if c == quote && !escaped => return true,
+                _ => escaped = false,
+            }
+            false
+        })
+        .map(|(i, _)| i + 1)
+        .ok_or((SyntaxError::Unclosed(unclosed), 0))
+}
+
+/// The length of the raw string literal (`r"..."`, `r#"..."#`, ...) at the start of `s`, if
+/// there is one.
+fn raw_string_len(s: &str) -> Option<Result<usize, ScanError>> {
+    let rest = s.strip_prefix('r')?;
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let body = rest[hashes..].strip_prefix('"')?;
+    let close = format!("\"{}", "#".repeat(hashes));
+    Some(
+        body.find(&close)
+            .map(|i| s.len() - body.len() + i + close.len())
+            .ok_or((SyntaxError::Unclosed("\""), 0)),
+    )
+}
+
+/// The length of the block or array literal at the start of `s` (or of a map literal, after its
+/// `#`). String, char literals and comments
+/// are skipped over, so brackets inside them don't count.
+fn delimited_len(s: &str) -> Result<usize, ScanError> {
+    let mut open = Vec::new();
+    let mut at_word_start = true;
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        if at_word_start {
+            if let Some(comment) = comment_len(&s[i..]) {
+                i += comment.map_err(|(e, at)| (e, i + at))?;
+                continue;
+            }
+        }
+        let mut len = c.len_utf8();
+        match c {
+            '[' => open.push((']', "[", i)),
+            '{' => open.push(('}', "{", i)),
+            ']' | '}' => match open.pop() {
+                Some((close, ..)) if close == c && open.is_empty() => return Ok(i + 1),
+                Some((close, ..)) if close == c => (),
+                Some((close, ..)) => {
+                    return Err((
+                        SyntaxError::Mismatched {
+                            expected: close,
+                            found: c,
+                        },
+                        i,
+                    ))
+                }
+                None => unreachable!("delimited tokens start with an opening bracket"),
+            },
+            '"' | '\'' => len = quoted_len(&s[i..]).map_err(|(e, at)| (e, i + at))?,
+            'r' if at_word_start => {
+                if let Some(raw) = raw_string_len(&s[i..]) {
+                    len = raw.map_err(|(e, at)| (e, i + at))?;
+                }
+            }
+            _ => (),
+        }
+        at_word_start = c.is_whitespace() || matches!(c, '[' | '{');
+        i += len;
     }
+    let (_, unclosed, at) = open.pop().expect("unclosed brackets were seen");
+    Err((SyntaxError::Unclosed(unclosed), at))
 }
 
 #[cfg(test)]
@@ -73,6 +351,7 @@ mod test {
         assert_eq!(
             r#"1 2 c 4.3 [ 1 2 3 ] [ [ [ 1 ] ] ] "ola amigos tudo bem" "#
                 .split_tokens()
+                .map(|t| t.unwrap().text)
                 .collect::<Vec<_>>(),
             vec![
                 "1",
@@ -85,4 +364,146 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn string_literals() {
+        assert_eq!(
+            r####""a \" b" r"c \" r#"d " e"# r##"f
+"# g"## 1"####
+                .split_tokens()
+                .map(|t| t.unwrap().text)
+                .collect::<Vec<_>>(),
+            vec![
+                r#""a \" b""#,
+                r#"r"c \""#,
+                r##"r#"d " e"#"##,
+                "r##\"f\n\"# g\"##",
+                "1"
+            ]
+        )
+    }
+
+    #[test]
+    fn spans() {
+        let spans = "1 { 2\n  [ 3 ] }"
+            .split_tokens()
+            .map(|t| t.unwrap().span)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![
+                Span {
+                    start: 0,
+                    end: 1,
+                    line: 1,
+                    column: 1
+                },
+                Span {
+                    start: 2,
+                    end: 15,
+                    line: 1,
+                    column: 3
+                },
+            ]
+        );
+        let block = "1 { 2\n  [ 3 ] }".split_tokens().nth(1).unwrap().unwrap();
+        let inner = block.inner().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(inner[1].text, "[ 3 ]");
+        assert_eq!(
+            inner[1].span,
+            Span {
+                start: 8,
+                end: 13,
+                line: 2,
+                column: 3
+            }
+        );
+        assert_eq!(inner[1].inner().next().unwrap().unwrap().span.start, 10);
+    }
+
+    #[test]
+    fn brackets_in_strings() {
+        assert_eq!(
+            r##"{ "}" p } [ "]" r"[" ] { r#"}"# }"##
+                .split_tokens()
+                .map(|t| t.unwrap().text)
+                .collect::<Vec<_>>(),
+            vec![r#"{ "}" p }"#, r#"[ "]" r"[" ]"#, r##"{ r#"}"# }"##]
+        )
+    }
+
+    #[test]
+    fn unbalanced() {
+        let error = |s: &str| match s.split_tokens().find_map(Result::err) {
+            Some(Error::Syntax(e, Some(span))) => (e, span.start),
+            e => panic!("expected a syntax error, got {:?}", e),
+        };
+        assert_eq!(error("1 [ 2 [ 3 ]"), (SyntaxError::Unclosed("["), 2));
+        assert_eq!(error(r#"{ "} p"#), (SyntaxError::Unclosed("\""), 2));
+        assert_eq!(error(r##"1 r#"a"##), (SyntaxError::Unclosed("\""), 2));
+        assert_eq!(error("1 2 ] 3"), (SyntaxError::Unmatched("]"), 4));
+        assert_eq!(
+            error("{ [ 1 } ]"),
+            (
+                SyntaxError::Mismatched {
+                    expected: ']',
+                    found: '}'
+                },
+                6
+            )
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            "1 -- one\n(* two (* nested ] *) *) { 2 -- } ]\n } [ (* ] *) 3 ] a--b"
+                .split_tokens()
+                .map(|t| t.unwrap().text)
+                .collect::<Vec<_>>(),
+            vec!["1", "{ 2 -- } ]\n }", "[ (* ] *) 3 ]", "a--b"]
+        );
+        assert!(matches!(
+            "1 (* (* *)".split_tokens().nth(1),
+            Some(Err(Error::Syntax(SyntaxError::Unclosed("(*"), _)))
+        ));
+        assert!(matches!(
+            "1 *) 2".split_tokens().nth(1),
+            Some(Err(Error::Syntax(SyntaxError::Unmatched("*)"), _)))
+        ));
+    }
+
+    #[test]
+    fn golf() {
+        fn golf(s: &str) -> Vec<&str> {
+            s.split_tokens_in(Mode::Golf)
+                .map(|t| t.unwrap().text)
+                .collect()
+        }
+        assert_eq!(golf("1 2+"), vec!["1", "2", "+"]);
+        assert_eq!(golf("{3*}%"), vec!["{3*}", "%"]);
+        assert_eq!(
+            golf("t S/{i}%1e&A:B;"),
+            vec!["t", "S/", "{i}", "%", "1", "e&", "A", ":B", ";"]
+        );
+        assert_eq!(golf("-1 5-1"), vec!["-1", "5", "-", "1"]);
+        assert_eq!(
+            golf("0xFF 1.5e3 2e<0b12"),
+            vec!["0xFF", "1.5e3", "2", "e<", "0b1", "2"]
+        );
+        assert_eq!(
+            golf("1_2$\"a\"'b'"),
+            vec!["1", "_", "2", "$", "\"a\"", "'b'"]
+        );
+        assert_eq!(golf("3--x\n-- y\n4"), vec!["3", "-", "-", "x", "4"]);
+        let block = "{1 1+}"
+            .split_tokens_in(Mode::Golf)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            block.inner().map(|t| t.unwrap().text).collect::<Vec<_>>(),
+            vec!["1", "1", "+"]
+        );
+    }
 }
@@ -0,0 +1,92 @@
+use crate::stack::Value;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// What counts as one character when taking the length of, indexing or slicing a string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Segmentation {
+    /// Unicode scalar values, the same as rust's `char`s.
+    #[default]
+    CodePoints,
+    /// Extended grapheme clusters, so that `"é"` written as an `e` and a combining accent is
+    /// still one character.
+    Graphemes,
+}
+
+impl Segmentation {
+    fn units(self, s: &str) -> Box<dyn DoubleEndedIterator<Item = (usize, &str)> + '_> {
+        match self {
+            Self::CodePoints => Box::new(
+                s.char_indices()
+                    .map(move |(i, c)| (i, &s[i..(i + c.len_utf8())])),
+            ),
+            Self::Graphemes => Box::new(s.grapheme_indices(true)),
+        }
+    }
+
+    /// The characters of `s`.
+    pub fn split(self, s: &str) -> impl DoubleEndedIterator<Item = &str> {
+        self.units(s).map(|(_, u)| u)
+    }
+
+    /// The string of the characters that the set operation `op` picks from those of `a` and `b`.
+    pub fn set_op<'s>(
+        self,
+        a: &'s str,
+        b: &'s str,
+        op: fn(Vec<&'s str>, Vec<&'s str>) -> Vec<&'s str>,
+    ) -> String {
+        op(self.split(a).collect(), self.split(b).collect()).concat()
+    }
+
+    pub fn len(self, s: &str) -> usize {
+        match self {
+            Self::CodePoints => s.chars().count(),
+            Self::Graphemes => s.graphemes(true).count(),
+        }
+    }
+
+    /// The `i`th character of `s`.
+    pub fn nth(self, s: &str, i: usize) -> Option<&str> {
+        self.units(s).nth(i).map(|(_, u)| u)
+    }
+
+    /// The byte offset where the `i`th character of `s` starts, or the length of `s` if it has
+    /// exactly `i` characters.
+    pub fn offset(self, s: &str, i: usize) -> Option<usize> {
+        let mut units = self.units(s);
+        match i.checked_sub(1) {
+            None => Some(0),
+            Some(last) => units.nth(last).map(|(at, u)| at + u.len()),
+        }
+    }
+
+    /// The index of the first character of `s` where `needle` starts, only counting matches made
+    /// of whole characters.
+    pub fn find(self, s: &str, needle: &str) -> Option<usize> {
+        let starts = self
+            .units(s)
+            .map(|(at, _)| at)
+            .chain([s.len()])
+            .collect::<Vec<_>>();
+        starts.iter().position(|&at| {
+            s[at..].starts_with(needle) && starts.binary_search(&(at + needle.len())).is_ok()
+        })
+    }
+
+    /// The byte offset where the `i`th character from the end of `s` starts.
+    pub fn offset_back(self, s: &str, i: usize) -> Option<usize> {
+        match i.checked_sub(1) {
+            None => Some(s.len()),
+            Some(last) => self.units(s).nth_back(last).map(|(at, _)| at),
+        }
+    }
+}
+
+/// A character taken out of a string: a `Char` if it is a single code point, a `Str` otherwise.
+pub fn unit_value(u: &str) -> Value {
+    let mut chars = u.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Value::Char(c),
+        _ => Value::from(u),
+    }
+}
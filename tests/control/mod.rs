@@ -0,0 +1,13 @@
+use crate::make_test;
+
+make_test!(if_values         : "1 2 3 ? 0 2 3 ?"                              => @[2, 3]);
+make_test!(if_blocks         : "1 { 2 } { 3 } ? 0 { 2 } { 3 } ?"              => @[2, 3]);
+make_test!(if_branch_not_run : "0 { 1 0 / } { 5 } ? 1 { 5 } { 1 0 / } ?"      => @[5, 5]);
+make_test!(if_empty_branch   : "1 { } 5 ?"                                    => @[]);
+make_test!(if_uses_stack     : "3 4 1 { + } { * } ? 3 4 0 { + } { * } ?"      => @[7, 12]);
+make_test!(recursion         : "{ _ 1 > { _ ( F ~ * } { ; 1 } ? } :F ; 5 F ~" => 120);
+make_test!(and_values        : "1 2 e& 0 2 e&"                                => @[2, 0]);
+make_test!(and_block         : "1 { 2 3 + } e& 0 { 1 0 / } e&"                => @[5, 0]);
+make_test!(or_values         : "1 2 e| 0 2 e|"                                => @[1, 2]);
+make_test!(or_block          : "0 { 2 3 + } e| 1 { 1 0 / } e|"                => @[5, 1]);
+make_test!(and_falsy_values  : "\"\" { 1 } e& [ ] { 1 } e| "                  => @["", 1]);
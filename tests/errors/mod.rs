@@ -0,0 +1,56 @@
+use crate::{make_error_test, make_test, util::on_big_stack};
+use std::{
+    error::Error,
+    io::{self, BufReader, Read},
+};
+
+make_test!(no_error        : "1 { 2 3 + } { ; 0 } try"            => @[1, 5]);
+make_test!(handler_runs    : "{ ; } { \"kind\" m= } try"          => "stack_empty");
+make_test!(error_message   : "{ 1 { } + } { \"message\" m= } try" => "cannot apply `add` to integer 1 and block { }");
+make_test!(restores_depth  : "1 2 { 3 4 + + + + } { ; 0 } try"    => @[1, 2, 0]);
+make_test!(restores_popped : "1 2 { ; ; [ ] ( } { ; } try"        => @[1, 2]);
+make_test!(default_to_zero : "{ l i } { ; 0 } try"                => 0 < "abc");
+make_test!(parses_input    : "{ l i } { ; 0 } try"                => 42 < "42");
+make_test!(kinds : "[ { 1 5 $ } { [ ] { + } * } { [ 1 ] c } { later } ] { { ~ } { \"kind\" m= \\ ; } try } % : later { 1 }"
+    => @[["out_of_bounds", "folding_empty_array", "invalid_cast", "undefined_word"]]);
+make_test!(throw_string     : "{ \"bad input\" throw } { _ \"kind\" m= \\ \"message\" m= } try" => @["thrown", "bad input"]);
+make_test!(throw_map        : "{ #[ \"kind\" \"mine\" \"code\" 7 ] throw } { \"code\" m= } try" => 7);
+make_test!(rethrow          : "{ { 5 throw } { throw } try } { \"message\" m= } try"            => "uncaught integer 5");
+make_test!(nested           : "{ { ; } { ; 1 } try 2 } { ; 0 } try"                             => @[1, 2]);
+make_test!(handler_value    : "{ ; } 7 try \\ \"kind\" m="                                      => @[7, "stack_empty"]);
+make_test!(body_value       : "3 7 try"                                                         => 3);
+make_test!(scopes_left      : "{ { 1 :v ; ; } ~ } { ; } try 5 :v ; v"                           => 5);
+
+#[test]
+fn word_depth_reset() {
+    on_big_stack(|| {
+        assert_eq!(
+            stack_machine::run(": down { _ { ( down } { } ? } { 5000 down } { ; } try 1200 down")
+                .unwrap(),
+            vec![stack_machine::Value::from(0)]
+        );
+    });
+}
+
+make_error_test!(uncaught_string     : "1 \"oops\" throw"      => "runtime error at 1:10: oops");
+make_error_test!(uncaught_array      : "[ 1 ] throw"           => "runtime error at 1:7: uncaught array [ 1 ]");
+make_error_test!(uncaught_in_handler : "{ 1 ; ; } { ; ; } try" => "runtime error at 1:15: the stack is empty");
+
+struct Broken;
+
+impl Read for Broken {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk on fire"))
+    }
+}
+
+#[test]
+fn io_error_chain() {
+    let e = stack_machine::run_with_input("l", &mut BufReader::new(Broken)).unwrap_err();
+    assert_eq!(e.to_string(), "runtime error at 1:1: failed to read input");
+    let runtime = e.source().expect("the runtime error is the source");
+    assert_eq!(runtime.to_string(), "failed to read input");
+    let io = runtime.source().expect("the io error is the source");
+    assert_eq!(io.to_string(), "disk on fire");
+    assert!(io.source().is_none());
+}
@@ -0,0 +1,41 @@
+use crate::{make_error_test, make_test, util::on_big_stack};
+use stack_machine::Value;
+
+make_test!(eval_string         : "\"1 2 +\" eval"                              => 3);
+make_test!(eval_uses_stack     : "4 \"_ *\" eval"                              => 16);
+make_test!(eval_block          : "{ 2 3 * } eval"                              => 6);
+make_test!(eval_input          : "t eval"                                      => [1, 2, 3] < "[ 1 2 3 ]");
+make_test!(eval_sees_words     : ": sq { _ * } 5 \"sq\" eval"                  => 25);
+make_test!(eval_sees_variables : "3 :size ; \"size size +\" eval"              => 6);
+make_test!(eval_defines_words  : "\": tw { 2 * }\" eval \"7 tw\" eval"         => 14);
+make_test!(unquote_pushes      : "\"1   +\" unquote quote"                     => "1 +");
+make_test!(unquote_runs_later  : "\"1 +\" unquote 5 \\ ~"                      => 6);
+make_test!(quote_block         : "{ 1 2 + } quote"                             => "1 2 +");
+make_test!(quote_nested        : "{ [ 1 { 2 * } ] -- note\n  #[ 1 2 ] } quote" => "[ 1 { 2 * } ] #[ 1 2 ]");
+make_test!(quote_values        : "[ 1 \"a\" 'b' 1.5 ] quote"                   => "[ 1 \"a\" 'b' 1.5 ]");
+make_test!(quote_definition    : "{ : sq { _ * } 2 sq } quote"                 => ": sq { _ * } 2 sq");
+make_test!(to_str_block        : "{ 3 { 4 } ~ } s"                             => "3 { 4 } ~");
+make_test!(round_trip          : "{ 1 2 { + } ~ } _ quote unquote ="           => 1);
+make_test!(round_trip_value    : "#[ \"k\" [ 1 2 ] ] _ quote eval ="           => 1);
+make_test!(syntax_error_caught : "{ \"1 bogus\" eval } { \"kind\" m= } try"    => "syntax");
+
+make_error_test!(eval_syntax_error         : "1 \"2 bogus\" eval"               => "runtime error at 1:13: syntax error in code at 1:3: unknown token `bogus`");
+make_error_test!(unquote_syntax_error      : "\"{ 1\" unquote"                  => "runtime error at 1:7: syntax error in code at 1:1: unclosed `{`");
+make_error_test!(eval_runtime_error        : "1 2 3 4 5 ; ; ; ; ; \"1 +\" eval" => "runtime error at 1:27: the stack is empty");
+make_error_test!(nested_eval_runtime_error : "\"\\\"1 +\\\" eval\" eval"        => "runtime error at 1:16: the stack is empty");
+make_error_test!(eval_integer              : "1 eval"                           => "runtime error at 1:3: cannot apply `eval` to integer 1");
+
+#[test]
+fn recursion() {
+    on_big_stack(|| {
+        let kind = |s| stack_machine::run(s).unwrap().pop().unwrap();
+        assert_eq!(
+            kind("\"code eval\" :code ; { code eval } { \"kind\" m= } try"),
+            Value::from("recursion_max")
+        );
+        assert_eq!(
+            kind("{ F ~ } :F ; { F ~ } { \"kind\" m= } try"),
+            Value::from("recursion_max")
+        );
+    });
+}
@@ -0,0 +1,38 @@
+use stack_machine::{run_with_input_and_mode, Mode, Value};
+use std::io::Cursor;
+
+macro_rules! golf_test {
+    ($name:ident: $input:expr => $exp:expr $(; < $stdin:expr)?) => {
+        #[test]
+        fn $name() {
+            #[allow(unused_variables)]
+            let stdin = "";
+            $(let stdin = $stdin;)?
+            assert_eq!(
+                run_with_input_and_mode($input, &mut Cursor::new(stdin), Mode::Golf)
+                    .unwrap()
+                    .pop(),
+                Some(Value::from($exp)),
+                "==> Test input was: '{}'",
+                $input
+            )
+        }
+    };
+}
+
+golf_test!(sum          : "1 2+"               => 3);
+golf_test!(map          : "[1 2 3]{3*}%"       => [3, 6, 9]);
+golf_test!(factorial    : "10,{)}%{*}*"        => 3628800);
+golf_test!(max_numbers  : "t S/{i}%{e>}*"      => 13; < "2 7 13 4");
+golf_test!(negatives    : "-1 5-1-"            => -7);
+golf_test!(vars         : "A B*:T;T"           => 110);
+golf_test!(copy         : "7 2 3 2$"           => 7);
+golf_test!(or           : "0 2e|"              => 2);
+golf_test!(words        : ": q{_*}5q"          => 25);
+golf_test!(eval         : "\"1 2+\"eval"       => 3);
+golf_test!(unquote      : "\"[1 2]{3*}%\"unquote~" => [3, 6]);
+
+#[test]
+fn spaced_is_default() {
+    assert!(stack_machine::run("1 2+").is_err());
+}
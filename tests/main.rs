@@ -1,2 +1,20 @@
+mod control;
 mod docenteslab2021;
+mod errors;
+mod eval;
+mod golf;
+mod maps;
+mod math;
+mod number_theory;
+mod numbers;
+mod order;
+mod program;
+mod sequences;
+mod sets;
+mod sharing;
+mod strings;
+mod syntax;
+mod unicode;
 pub mod util;
+mod variables;
+mod words;
@@ -0,0 +1,26 @@
+use crate::{make_error_test, make_test};
+
+make_test!(literal        : "#[ \"a\" 1 \"b\" 2 ] s"               => "#[ \"a\" 1 \"b\" 2 ]");
+make_test!(empty          : "#[ ] , #[ ] !"                        => @[0, 1]);
+make_test!(computed       : "#[ 1 2 + 'x' 1 1 + 'y' ] 3 m="        => 'x');
+make_test!(get            : "#[ \"a\" 1 \"b\" 2 ] \"b\" m="        => 2);
+make_test!(insert         : "#[ \"a\" 1 ] \"b\" 2 m+ \"a\" 3 m+ s" => "#[ \"a\" 3 \"b\" 2 ]");
+make_test!(remove         : "#[ \"a\" 1 \"b\" 2 ] \"a\" m- s"      => "#[ \"b\" 2 ]");
+make_test!(remove_missing : "#[ \"a\" 1 ] \"z\" m- ,"              => 1);
+make_test!(has_key        : "#[ 1 2 ] 1 m? #[ 1 2 ] 2 m?"          => @[1, 0]);
+make_test!(numeric_keys   : "#[ 1 'a' ] 1.0 m="                    => 'a');
+make_test!(keys           : "#[ \"a\" 1 \"b\" 2 ] mk"              => @[["a", "b"]]);
+make_test!(values         : "#[ \"a\" 1 \"b\" 2 ] mv"              => @[[1, 2]]);
+make_test!(length         : "#[ 1 2 3 4 5 6 ] ,"                   => 3);
+make_test!(merge          : "#[ 1 2 3 4 ] #[ 3 5 6 7 ] + s"        => "#[ 1 2 3 5 6 7 ]");
+make_test!(spread         : "#[ 1 2 3 4 ] ~"                       => @[[1, 2], [3, 4]]);
+make_test!(map_entries    : "#[ 1 2 3 4 ] { 10 * } % s"            => "#[ 1 20 3 40 ]");
+make_test!(map_keys       : "#[ 1 2 3 4 ] { \\ ) \\ } % mk"        => @[[2, 4]]);
+make_test!(equal          : "#[ 1 2 3 4 ] #[ 3 4 1 2 ] ="          => 1);
+make_test!(nested         : "#[ \"m\" #[ 1 2 ] ] \"m\" m= 1 m="    => 2);
+
+make_error_test!(get_missing_type       : "#[ 1 2 ] 3 m="    => "runtime error at 1:12: cannot apply `get` to map #[ 1 2 ] and integer 3");
+make_error_test!(odd_map_literal        : "#[ 1 2 3 ]"       => "runtime error at 1:1: cannot apply `map_literal` to array [ 1 2 3 ]");
+make_error_test!(map_entry_size         : "#[ 1 2 ] { ; } %" => "runtime error at 1:16: cannot apply `map_entry` to array [ 1 ]");
+make_error_test!(insert_into_integer    : "1 2 3 m+"         => "runtime error at 1:7: cannot apply `insert` to integer 1, integer 2 and integer 3");
+make_error_test!(mismatched_map_literal : "1 #[ 1 2 }"       => "syntax error at 1:10: expected `]` but found `}`");
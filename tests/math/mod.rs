@@ -0,0 +1,22 @@
+use crate::{make_error_test, make_test};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+make_test!(sqrt         : "16 sqrt 2.25 sqrt 1/4 sqrt"                                  => @[4.0, 1.5, 0.5]);
+make_test!(abs          : "-5 abs 5 abs -2.5 abs -3/4 abs s"                            => @[5, 5, 2.5, "3/4"]);
+make_test!(abs_promotes : "-9223372036854775808 abs s"                                  => "9223372036854775808");
+make_test!(sign         : "-7 sign 0 sign 3/4 sign -0.5 sign 99999999999999999999 sign" => @[-1, 0, 1, -1, 1]);
+make_test!(floor        : "2.7 floor -2.5 floor 7/2 floor 4 floor"                      => @[2, -3, 3, 4]);
+make_test!(ceil         : "2.1 ceil -2.5 ceil 7/2 ceil"                                 => @[3, -2, 4]);
+make_test!(round        : "2.5 round -2.5 round 2.4 round 5/2 round"                    => @[3, -3, 2, 3]);
+make_test!(round_big    : "1e20 round s"                                                => "100000000000000000000");
+make_test!(trig         : "0 sin 0 cos 0 tan 1 asin 1 acos 1 atan"                      => @[0.0, 1.0, 0.0, FRAC_PI_2, 0.0, FRAC_PI_4]);
+make_test!(logs         : "1 ln 1000 log 0 exp"                                         => @[0.0, 3.0, 1.0]);
+make_test!(float_pow    : "4 0.5 # 2.0 3 # 4 1/2 # 8 -1.0 #"                            => @[2.0, 8.0, 2.0, 0.125]);
+make_test!(integer_pow  : "2 10 # 2/3 2 # s"                                            => @[1024, "4/9"]);
+make_test!(domain_kind  : "{ -1 sqrt } { \"kind\" m= } try"                             => "domain");
+
+make_error_test!(sqrt_negative     : "-4 sqrt"    => "runtime error at 1:4: `sqrt` is not defined for integer -4");
+make_error_test!(ln_zero           : "0 ln"       => "runtime error at 1:3: `ln` is not defined for integer 0");
+make_error_test!(asin_out_of_range : "2 asin"     => "runtime error at 1:3: `asin` is not defined for integer 2");
+make_error_test!(pow_negative_root : "-8.0 1/3 #" => "runtime error at 1:10: `pow` is not defined for float -8.0 and rational 1/3");
+make_error_test!(sqrt_string       : "\"a\" sqrt" => "runtime error at 1:5: cannot apply `sqrt` to string \"a\"");
@@ -0,0 +1,28 @@
+use crate::{make_error_test, make_test};
+
+make_test!(gcd               : "12 18 gcd -4 6 gcd 0 0 gcd 7 0 gcd"               => @[6, 2, 0, 7]);
+make_test!(lcm               : "4 6 lcm -3 5 lcm 0 5 lcm"                         => @[12, 15, 0]);
+make_test!(lcm_promotes      : "9223372036854775807 2 lcm s"                      => "18446744073709551614");
+make_test!(gcd_big           : "2 100 # 6 70 # gcd 2 70 # ="                      => 1);
+make_test!(prime             : "[ -7 0 1 2 3 4 97 91 ] { prime } %"               => @[[0, 0, 0, 1, 1, 0, 1, 0]]);
+make_test!(prime_big         : "2 127 # ( prime 2 128 # ) prime"                  => @[1, 0]);
+make_test!(prime_i64         : "9223372036854775783 prime"                        => 1);
+make_test!(primes_below      : "100 , { prime } , ,"                              => 25);
+make_test!(factor            : "360 factor"                                       => @[[2, 2, 2, 3, 3, 5]]);
+make_test!(factor_one        : "1 factor , 13 factor"                             => @[0, [13]]);
+make_test!(factor_semiprime  : "1000003 1000033 * factor"                         => @[[1000003, 1000033]]);
+make_test!(factor_big        : "2 64 # ) factor"                                  => @[[274177, 67280421310721i64]]);
+make_test!(to_digits         : "255 16 base 10 2 base 0 7 base"                   => @[[15, 15], [1, 0, 1, 0], [0]]);
+make_test!(from_digits       : "[ 1 0 1 0 ] 2 base [ 15 15 ] 16 base [ ] 10 base" => @[10, 255, 0]);
+make_test!(digits_round_trip : "2 100 # 1000 base 1000 base 2 100 # ="            => 1);
+make_test!(radix_strings     : "255 hex 8 oct 5 bin -255 hex"                     => @["ff", "10", "101", "-ff"]);
+make_test!(hex_big           : "2 64 # hex"                                       => "10000000000000000");
+
+make_error_test!(factor_zero      : "0 factor"                             => "runtime error at 1:3: `factor` is not defined for integer 0");
+make_error_test!(base_one         : "10 1 base"                            => "runtime error at 1:6: `base` is not defined for integer 10 and integer 1");
+make_error_test!(base_negative    : "-10 2 base"                           => "runtime error at 1:7: `base` is not defined for integer -10 and integer 2");
+make_error_test!(digit_too_big    : "[ 1 12 ] 10 base"                     => "runtime error at 1:13: `base` is not defined for array [ 1 12 ] and integer 10");
+make_error_test!(digit_negative   : "[ 1 -1 ] 10 base"                     => "runtime error at 1:13: `base` is not defined for array [ 1 -1 ] and integer 10");
+make_error_test!(factor_too_large : "1099511627791 1099511627803 * factor" => "runtime error at 1:31: cannot apply `factor_too_large` to integer 1208925819660808663073173");
+make_error_test!(prime_float      : "1.5 prime"                            => "runtime error at 1:5: cannot apply `prime` to float 1.5");
+make_error_test!(gcd_string       : "\"a\" 2 gcd"                          => "runtime error at 1:7: cannot apply `gcd` to string \"a\" and integer 2");
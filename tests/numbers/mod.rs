@@ -0,0 +1,229 @@
+use crate::{make_error_test, make_test};
+use stack_machine::{Overflow, Stack, Value};
+use std::io;
+
+const FACT_30: &str = "265252859812191058636308480000000";
+
+make_test!(factorial    : "30 , { ) } % { * } * s"                     => FACT_30);
+make_test!(add_overflow : "9223372036854775807 1 + s"                  => "9223372036854775808");
+make_test!(sub_overflow : "-9223372036854775808 1 - s"                 => "-9223372036854775809");
+make_test!(div_overflow : "-9223372036854775808 -1 / s"                => "9223372036854775808");
+make_test!(demote       : "9223372036854775807 1 + 2 -"                => 9223372036854775806i64);
+make_test!(big_literal  : "100000000000000000000 3 /"                  => 33333333333333333333i128);
+make_test!(hex_literal  : "0x8000000000000000"                         => 9223372036854775808u64);
+make_test!(pow          : "2 100 # s"                                  => "1267650600228229401496703205376");
+make_test!(pow_big_base : "2 64 # 2 # 2 128 # ="                       => 1);
+make_test!(compare      : "2 64 # 2 63 # < 2 64 # 1e30 <"              => @[0, 1]);
+make_test!(to_int       : "\"123456789012345678901234567890\" i 1 + s" => "123456789012345678901234567891");
+make_test!(float_to_int : "1e20 i s"                                   => "100000000000000000000");
+make_test!(to_float     : "2 70 # f"                                   => 1180591620717411303424.0);
+make_test!(big_rem      : "2 70 # 7 %"                                 => 2);
+make_test!(big_bitand   : "2 70 # 1 - 255 &"                           => 255);
+make_test!(big_not      : "2 70 # ~ 2 70 # + "                         => -1);
+
+make_error_test!(big_division_by_zero : "2 70 # 0 /" => "runtime error at 1:10: cannot apply `div` to integer 1180591620717411303424 and integer 0");
+
+make_test!(exact_div         : "1 3 e/ 1 6 e/ + s"           => "1/2");
+make_test!(rational_whole    : "1 3 e/ 3 *"                  => 1);
+make_test!(rational_literal  : "3/4 2 * s"                   => "3/2");
+make_test!(rational_negative : "-6/8 s"                      => "-3/4");
+make_test!(rational_pow      : "1/2 2 # s 2/3 -2 # s"        => @["1/4", "9/4"]);
+make_test!(rational_float    : "3/4 0.25 +"                  => 1.0);
+make_test!(rational_to_int   : "7/2 i -7/2 i"                => @[3, -3]);
+make_test!(rational_to_float : "3/4 f"                       => 0.75);
+make_test!(rational_compare  : "1/3 1/2 < 1/2 0.4 > 2 3/2 >" => @[1, 1, 1]);
+make_test!(rational_rem      : "7/2 1 % s"                   => "1/2");
+make_test!(rational_div      : "1/2 1/4 /"                   => 2);
+make_test!(exact_div_float   : "1.0 4 e/"                    => 0.25);
+
+make_error_test!(exact_div_by_zero        : "1 0 e/" => "runtime error at 1:5: cannot apply `exact_div` to integer 1 and integer 0");
+make_error_test!(rational_literal_by_zero : "1/0"    => "syntax error at 1:1: invalid number literal `1/0`");
+
+fn run_with(overflow: Overflow, s: &str) -> Result<Vec<Value>, stack_machine::Error> {
+    let mut input = io::empty();
+    let mut stack = Stack::with_input(&mut input);
+    stack.set_overflow(overflow);
+    stack_machine::run_on(s, stack)
+}
+
+#[test]
+fn overflow_policies() {
+    const MAX: &str = "9223372036854775807";
+    const MIN: &str = "-9223372036854775808";
+    let cases = [
+        (
+            format!("{} 1 +", MAX),
+            i64::MIN,
+            i64::MAX,
+            9223372036854775808.0,
+        ),
+        (
+            format!("{} 1 -", MIN),
+            i64::MAX,
+            i64::MIN,
+            -9223372036854775808.0,
+        ),
+        (format!("{} 2 *", MAX), -2, i64::MAX, 18446744073709551614.0),
+        (
+            format!("{} -1 /", MIN),
+            i64::MIN,
+            i64::MAX,
+            9223372036854775808.0,
+        ),
+        ("2 64 #".into(), 0, i64::MAX, 18446744073709551616.0),
+        (
+            format!("{} )", MAX),
+            i64::MIN,
+            i64::MAX,
+            9223372036854775808.0,
+        ),
+        (
+            format!("{} (", MIN),
+            i64::MAX,
+            i64::MIN,
+            -9223372036854775808.0,
+        ),
+        (
+            format!("{} -1 e/", MIN),
+            i64::MIN,
+            i64::MAX,
+            9223372036854775808.0,
+        ),
+        (
+            format!("3/2 {} * 2 *", MAX),
+            9223372036854775805,
+            i64::MAX,
+            27670116110564327421.0,
+        ),
+        (
+            "18446744073709551615/2 2 *".into(),
+            -1,
+            i64::MAX,
+            18446744073709551615.0,
+        ),
+        ("1e19 i".into(), -8446744073709551616, i64::MAX, 1e19),
+        (
+            "\"10000000000000000000\" i".into(),
+            -8446744073709551616,
+            i64::MAX,
+            1e19,
+        ),
+        ("1e19 round".into(), -8446744073709551616, i64::MAX, 1e19),
+        (
+            "20000000000000000001/2 floor".into(),
+            -8446744073709551616,
+            i64::MAX,
+            1e19,
+        ),
+        (
+            "10000000000000000000".into(),
+            -8446744073709551616,
+            i64::MAX,
+            1e19,
+        ),
+    ];
+    for (program, wrapping, saturating, float) in cases {
+        assert!(
+            run_with(Overflow::Error, &program).is_err(),
+            "{} didn't error",
+            program
+        );
+        assert_eq!(
+            run_with(Overflow::Wrapping, &program).unwrap(),
+            vec![Value::from(wrapping)],
+            "{} wrapping",
+            program
+        );
+        assert_eq!(
+            run_with(Overflow::Saturating, &program).unwrap(),
+            vec![Value::from(saturating)],
+            "{} saturating",
+            program
+        );
+        assert_eq!(
+            run_with(Overflow::Float, &program).unwrap(),
+            vec![Value::from(float)],
+            "{} float",
+            program
+        );
+    }
+}
+
+#[test]
+fn pow_limits() {
+    assert_eq!(
+        stack_machine::run("3 100000000 #").unwrap_err().to_string(),
+        "runtime error at 1:13: cannot apply `pow_too_large` to integer 3 and integer 100000000"
+    );
+    assert_eq!(
+        stack_machine::run("3/2 10000000 #")
+            .unwrap_err()
+            .to_string(),
+        "runtime error at 1:14: cannot apply `pow_too_large` to rational 3/2 and integer 10000000"
+    );
+    assert_eq!(
+        stack_machine::run("2 100000 # 2 99999 # / 1 100000000 # -1 100000001 #").unwrap(),
+        vec![Value::from(2), Value::from(1), Value::from(-1)]
+    );
+    assert!(run_with(Overflow::Error, "3 100000000 #").is_err());
+    assert_eq!(
+        run_with(Overflow::Wrapping, "3 100000000 #").unwrap(),
+        vec![Value::from(5478338451055735809i64)]
+    );
+    assert_eq!(
+        run_with(Overflow::Saturating, "-3 100000001 #").unwrap(),
+        vec![Value::from(i64::MIN)]
+    );
+    assert_eq!(
+        run_with(Overflow::Float, "3 100000000 #").unwrap(),
+        vec![Value::from(f64::INFINITY)]
+    );
+}
+
+#[test]
+fn overflow_error() {
+    assert_eq!(
+        run_with(Overflow::Error, "9223372036854775807 1 +")
+            .unwrap_err()
+            .to_string(),
+        "runtime error at 1:23: cannot apply `add` to integer 9223372036854775807 and integer 1"
+    );
+    for (program, message) in [
+        ("10000000000000000000", "runtime error at 1:1: cannot convert integer 10000000000000000000 to i64"),
+        ("1e19 i", "runtime error at 1:6: cannot convert float 1e19 to i64"),
+        ("1e19 round", "runtime error at 1:6: cannot apply `round` to float 1e19"),
+        ("18446744073709551615/2 2 *", "runtime error at 1:26: cannot apply `mul` to rational 18446744073709551615/2 and integer 2"),
+    ] {
+        assert_eq!(
+            run_with(Overflow::Error, program).unwrap_err().to_string(),
+            message
+        );
+    }
+    assert_eq!(
+        run_with(Overflow::Error, "2 62 # 1 - 2 * 1 + 9223372036854775807 =").unwrap(),
+        vec![Value::from(1)]
+    );
+    assert_eq!(
+        run_with(Overflow::Wrapping, "100000000000000000000 1 +").unwrap(),
+        vec![Value::from(7766279631452241921i64)]
+    );
+}
+
+make_test!(repeat_array : "[ 1 2 ] 3 * [ 1 2 ] 0 * [ ] 5 *" => @[[1, 2, 1, 2, 1, 2], Vec::<i64>::new(), Vec::<i64>::new()]);
+make_test!(repeat_str   : "\"ab\" 3 * \"ab\" 0 *"           => @["ababab", ""]);
+
+#[test]
+fn repeat_limits() {
+    for program in [
+        "[ 1 ] -1 *",
+        "\"a\" -1 *",
+        "[ 1 2 ] 100000000 *",
+        "\"ab\" 2 62 # *",
+    ] {
+        assert!(
+            stack_machine::run(program).is_err(),
+            "{} didn't error",
+            program
+        );
+    }
+}
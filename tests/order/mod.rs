@@ -0,0 +1,52 @@
+use crate::make_test;
+use stack_machine::Value;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+make_test!(numbers_equal : "1 1.0 = 1 2/2 = 2 64 # 2 64 # f ="                     => @[1, 1, 1]);
+make_test!(float_exact   : "0.1 1/10 > 0.5 1/2 ="                                  => @[1, 1]);
+make_test!(by_kind       : "1 'a' < 'a' \"a\" < \"a\" [ ] < [ ] #[ ] < #[ ] { } <" => @[1, 1, 1, 1, 1]);
+make_test!(nan           : "nan nan = nan inf > -inf -9223372036854775809 <"       => @[1, 1, 1]);
+make_test!(negative_zero : "-0.0 0 ="                                              => 1);
+make_test!(blocks        : "{ 1 } { 1 2 } = { 1 } { 1 2 } < { 2 } { 1 2 } >"       => @[0, 1, 1]);
+make_test!(sort_mixed : "[ \"b\" 2.5 'a' nan 1 [ 1 ] -inf 3/2 ] { } $" => @[[
+    Value::Float(f64::NEG_INFINITY),
+    Value::from(1),
+    Value::from(1.5),
+    Value::from(2.5),
+    Value::Float(f64::NAN),
+    Value::from('a'),
+    Value::from("b"),
+    Value::from([1]),
+]]);
+
+fn hash(v: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    v.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn equal_values_hash_equal() {
+    let groups = [
+        "[ 1 1.0 2/2 ]",
+        "[ 2 64 # 2 64 # f ]",
+        "[ 0.5 1/2 ]",
+        "[ 0 -0.0 ]",
+        "[ nan nan ]",
+        "[ #[ 1 2 ] #[ 1.0 2 ] ]",
+        "[ [ 1 'a' ] [ 1.0 'a' ] ]",
+    ];
+    for group in groups {
+        let values = match &stack_machine::run(group).unwrap()[..] {
+            [Value::Array(a)] => a.clone(),
+            v => panic!("{} didn't make an array: {:?}", group, v),
+        };
+        for v in &values {
+            assert_eq!(v, &values[0], "in {}", group);
+            assert_eq!(hash(v), hash(&values[0]), "in {}", group);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::make_error_test;
+use stack_machine::{Error, Program, Span, Value};
+use std::io::Cursor;
+
+#[test]
+fn runs_many_times() {
+    let program = Program::parse("l i l i #").unwrap();
+    for (input, exp) in [("2\n4", 16), ("3\n2", 9), ("10\n0", 1)] {
+        assert_eq!(
+            program.run_with_input(&mut Cursor::new(input)).unwrap(),
+            vec![Value::from(exp)]
+        );
+    }
+}
+
+#[test]
+fn syntax_errors_before_running() {
+    assert!(matches!(
+        Program::parse("l p [ 1 2 { 3 bogus } ]"),
+        Err(Error::Syntax(
+            _,
+            Some(Span {
+                start: 14,
+                end: 19,
+                line: 1,
+                column: 15
+            })
+        ))
+    ));
+}
+
+#[test]
+fn array_literals_are_reevaluated() {
+    let program = Program::parse("[ l l ]").unwrap();
+    assert_eq!(
+        program.run_with_input(&mut Cursor::new("a\nb")).unwrap(),
+        vec![Value::from(["a", "b"])]
+    );
+    assert_eq!(
+        program.run_with_input(&mut Cursor::new("c\nd")).unwrap(),
+        vec![Value::from(["c", "d"])]
+    );
+}
+
+#[test]
+fn runtime_error_points_at_operator() {
+    let e = stack_machine::run("1 2 +\n[ { 1 1 + } { \"a\" 2 - } ] { ~ } %").unwrap_err();
+    assert!(matches!(e, Error::Runtime(..)));
+    assert_eq!(
+        e.span(),
+        Some(Span {
+            start: 26,
+            end: 27,
+            line: 2,
+            column: 21
+        })
+    );
+}
+
+make_error_test!(add_block_error       : "3 { + } +"       => "runtime error at 1:9: cannot apply `add` to integer 3 and block { + }");
+make_error_test!(empty_on_second_line  : "1 ;\n;"          => "runtime error at 2:1: the stack is empty");
+make_error_test!(unknown_in_array      : "[ bogus ] i"     => "syntax error at 1:3: unknown token `bogus`");
+make_error_test!(convert_array_to_char : "[ 1.5 \"a\" ] c" => "runtime error at 1:13: cannot convert array [ 1.5 \"a\" ] to char");
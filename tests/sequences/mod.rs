@@ -0,0 +1,24 @@
+use crate::{make_error_test, make_test, v};
+
+make_test!(scan             : "[ 1 2 3 4 ] { + } scan"                      => @[[1, 3, 6, 10]]);
+make_test!(scan_empty       : "[ ] { + } scan ,"                            => 0);
+make_test!(scan_max         : "[ 3 1 4 1 5 ] { e> } scan"                   => @[[3, 3, 4, 4, 5]]);
+make_test!(scan_then_fold   : "[ 1 2 3 ] { * } scan { + } *"                => 9);
+make_test!(zip              : "[ 1 2 3 ] [ 'a' 'b' 'c' ] zip"               => @[[v!([v!(1), v!('a')]), v!([v!(2), v!('b')]), v!([v!(3), v!('c')])]]);
+make_test!(zip_shortest     : "[ 1 2 3 ] [ 4 ] zip"                         => @[[[1, 4]]]);
+make_test!(zipwith          : "[ 1 2 3 ] [ 10 20 30 ] { + } zipwith"        => @[[11, 22, 33]]);
+make_test!(zipwith_order    : "[ 10 20 ] [ 1 2 ] { - } zipwith"             => @[[9, 18]]);
+make_test!(windows          : "[ 1 2 3 4 ] 2 windows"                       => @[[[1, 2], [2, 3], [3, 4]]]);
+make_test!(windows_too_wide : "[ 1 2 ] 3 windows ,"                         => 0);
+make_test!(windows_sums     : "[ 1 2 3 4 5 ] 3 windows { { + } * } %"       => @[[6, 9, 12]]);
+make_test!(windows_string   : "\"abcd\" 3 windows"                          => @[["abc", "bcd"]]);
+make_test!(chunks           : "[ 1 2 3 4 5 ] 2 chunks"                      => @[[v!([1, 2]), v!([3, 4]), v!([5])]]);
+make_test!(chunks_string    : "\"ação!\" 2 chunks"                          => @[["aç", "ão", "!"]]);
+make_test!(transpose        : "[ [ 1 2 3 ] [ 4 5 6 ] ] transpose"           => @[[[1, 4], [2, 5], [3, 6]]]);
+make_test!(transpose_twice  : "[ [ 1 2 ] [ 3 4 ] ] _ transpose transpose =" => 1);
+make_test!(transpose_empty  : "[ ] transpose ,"                             => 0);
+
+make_error_test!(windows_zero        : "[ 1 2 ] 0 windows"             => "runtime error at 1:11: cannot apply `windows` to array [ 1 2 ] and integer 0");
+make_error_test!(transpose_ragged    : "[ [ 1 2 ] [ 3 ] ] transpose"   => "runtime error at 1:19: cannot apply `transpose` to array [ [ 1 2 ] [ 3 ] ]");
+make_error_test!(zipwith_integer     : "[ 1 ] 2 { + } zipwith"         => "runtime error at 1:15: cannot apply `zipwith` to array [ 1 ], integer 2 and block { + }");
+make_error_test!(zipwith_stack_empty : "[ 1 2 ] [ 3 ] { + + } zipwith" => "runtime error at 1:19: the stack is empty");
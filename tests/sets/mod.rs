@@ -0,0 +1,20 @@
+use crate::{make_error_test, make_test};
+
+make_test!(union                       : "[ 1 2 2 3 ] [ 4 3 1 5 ] |"           => @[[1, 2, 3, 4, 5]]);
+make_test!(intersection                : "[ 3 1 2 1 ] [ 1 3 4 ] &"             => @[[3, 1]]);
+make_test!(symmetric_difference        : "[ 1 2 3 2 ] [ 4 3 4 5 ] ^"           => @[[1, 2, 4, 5]]);
+make_test!(difference                  : "[ 1 2 3 1 2 ] [ 2 ] -"               => @[[1, 3, 1]]);
+make_test!(empty                       : "[ 1 2 ] [ ] & [ ] [ 1 1 ] |"         => @[Vec::<i64>::new(), vec![1]]);
+make_test!(mixed_elements              : "[ 1 \"a\" [ 2 ] ] [ [ 2 ] 1.0 ] & ~" => @[1, [2]]);
+make_test!(string_union                : "\"hello\" \"world\" |"               => "helowrd");
+make_test!(string_intersection         : "\"hello\" \"world\" &"               => "lo");
+make_test!(string_symmetric_difference : "\"hello\" \"world\" ^"               => "hewrd");
+make_test!(string_difference           : "\"banana\" \"n\" -"                  => "baaa");
+make_test!(numbers_unchanged           : "6 3 & 6 3 | 6 3 ^ 6 3 -"             => @[2, 7, 5, 3]);
+make_test!(unique_array                : "[ 3 1 3 2 1 ] u"                     => @[[3, 1, 2]]);
+make_test!(unique_string               : "\"mississippi\" u"                   => "misp");
+make_test!(unique_words                : "\"a b a c b\" S/ u"                  => @[["a", "b", "c"]]);
+make_test!(unique_numbers              : "[ 1 1.0 2/2 2 ] u"                   => @[[1, 2]]);
+
+make_error_test!(array_and_string : "[ 1 ] \"a\" &" => "runtime error at 1:11: cannot apply `bitand` to array [ 1 ] and string \"a\"");
+make_error_test!(unique_integer   : "1 u"           => "runtime error at 1:3: cannot apply `unique` to integer 1");
@@ -0,0 +1,52 @@
+use crate::make_test;
+use stack_machine::{Shared, Stack, Value};
+use std::io;
+
+make_test!(dup_then_push       : "[ 1 2 ] _ 3 +"                => @[[1, 2], [1, 2, 3]]);
+make_test!(dup_then_pop        : "\"abc\" _ )"                  => @["abc", "ab", 'c']);
+make_test!(var_then_change     : "[ 1 2 ] :A ; A 0 + A"         => @[[1, 2, 0], [1, 2]]);
+make_test!(nth_then_slice      : "[ 1 2 3 ] 0 $ 2 <"            => @[[1, 2, 3], [1, 2]]);
+make_test!(filter_keeps_source : "[ 1 2 3 4 ] :A { 2 % } , A ," => @[[1, 3], 4]);
+make_test!(map_keeps_source    : "\"ab\" :A { 1 + } % A"        => @["bc", "ab"]);
+
+fn arrays(values: Vec<Value>) -> Vec<Shared<Vec<Value>>> {
+    values
+        .into_iter()
+        .map(|v| match v {
+            Value::Array(a) => a,
+            v => panic!("expected an array, was {:?}", v),
+        })
+        .collect()
+}
+
+/// Runs `s` on a stack that only has `a` on it.
+fn run_on_array(a: &Shared<Vec<Value>>, s: &str) -> Vec<Shared<Vec<Value>>> {
+    let mut input = io::empty();
+    let mut stack = Stack::with_input(&mut input);
+    stack.push(Value::Array(a.clone()));
+    arrays(stack_machine::run_on(s, stack).unwrap())
+}
+
+#[test]
+fn reads_share() {
+    for program in ["[ 1 2 ] _", "[ 1 2 ] :A ; A A", "[ 1 2 ] 0 $"] {
+        let a = arrays(stack_machine::run(program).unwrap());
+        assert!(Shared::ptr_eq(&a[0], &a[1]), "{} copied", program);
+    }
+    match &stack_machine::run("\"abc\" _").unwrap()[..] {
+        [Value::Str(a), Value::Str(b)] => assert!(Shared::ptr_eq(a, b)),
+        v => panic!("expected two strings, was {:?}", v),
+    }
+}
+
+#[test]
+fn copies_on_mutation() {
+    let original = Shared::new(vec![Value::from(1), Value::from(2)]);
+    let read = run_on_array(&original, "_ ;");
+    assert!(Shared::ptr_eq(&read[0], &original));
+    let changed = run_on_array(&original, "3 +");
+    assert!(!Shared::ptr_eq(&changed[0], &original));
+    assert_eq!(*original, vec![Value::from(1), Value::from(2)]);
+    let a = arrays(stack_machine::run("[ 1 2 ] _ 3 +").unwrap());
+    assert!(!Shared::ptr_eq(&a[0], &a[1]));
+}
@@ -0,0 +1,28 @@
+use crate::{make_error_test, make_test};
+
+make_test!(upper             : "\"Olá, mundo\" upper"                                => "OLÁ, MUNDO");
+make_test!(lower             : "\"ÁRVORE Grande\" lower"                             => "árvore grande");
+make_test!(upper_char        : "'a' upper 'ß' upper"                                 => @['A', "SS"]);
+make_test!(trim              : "\"  a b \n\" trim"                                   => "a b");
+make_test!(replace           : "\"a-b-c\" \"-\" \", \" replace"                      => "a, b, c");
+make_test!(replace_with_char : "\"a b c\" \" \" '_' replace"                         => "a_b_c");
+make_test!(replace_missing   : "\"abc\" \"x\" \"y\" replace"                         => "abc");
+make_test!(join              : "[ \"a\" \"b\" \"c\" ] \", \" join"                   => "a, b, c");
+make_test!(join_values       : "[ 1 'x' 2.5 ] '-' join"                              => "1-x-2.5");
+make_test!(join_empty        : "[ ] \",\" join"                                      => "");
+make_test!(split_and_join    : "\"a b  c\" S/ \"+\" join"                            => "a+b+c");
+make_test!(starts            : "\"banana\" \"ban\" starts \"banana\" \"nan\" starts" => @[1, 0]);
+make_test!(ends              : "\"banana\" \"ana\" ends \"banana\" 'b' ends"         => @[1, 0]);
+make_test!(reverse           : "\"ação\" reverse"                                    => "oãça");
+make_test!(reverse_array     : "[ 1 2 3 ] reverse"                                   => @[[3, 2, 1]]);
+make_test!(pad_left          : "\"7\" 3 '0' padl"                                    => "007");
+make_test!(pad_right         : "\"ab\" 5 \".\" padr"                                 => "ab...");
+make_test!(pad_number        : "42 5 S padl"                                         => "   42");
+make_test!(pad_wider         : "\"abcdef\" 3 '*' padl"                               => "abcdef");
+make_test!(pad_unicode       : "\"ção\" 5 '-' padr"                                  => "ção--");
+
+make_error_test!(upper_integer   : "1 upper"                  => "runtime error at 1:3: cannot apply `upper` to integer 1");
+make_error_test!(replace_empty   : "\"a\" \"\" \"b\" replace" => "runtime error at 1:12: cannot apply `replace` to string \"a\", string \"\" and string \"b\"");
+make_error_test!(pad_negative    : "\"a\" -1 ' ' padl"        => "runtime error at 1:12: cannot apply `pad` to string \"a\", integer -1 and char ' '");
+make_error_test!(pad_with_string : "\"a\" 3 \"xy\" padl"      => "runtime error at 1:12: cannot apply `pad` to string \"a\", integer 3 and string \"xy\"");
+make_error_test!(join_string     : "\"a\" 1 join"             => "runtime error at 1:7: cannot apply `join` to string \"a\" and integer 1");
@@ -0,0 +1,52 @@
+use crate::make_test;
+
+make_test!(escapes       : r#""a\"b\\c\td\n""#                   => "a\"b\\c\td\n");
+make_test!(unicode       : r#""\u{e9}t\u{E9}""#                  => "été");
+make_test!(quote_length  : r#""\"\"" ,"#                         => 2);
+make_test!(raw           : r#"r"a\nb""#                          => "a\\nb");
+make_test!(raw_hashes    : r###"r#"say "hi""#"###                => "say \"hi\"");
+make_test!(multi_line    : "\"ola\nadeus\" N/ ,"                 => 2);
+make_test!(str_in_block  : r#"{ "}" } ~"#                        => "}");
+make_test!(str_in_array  : r#"[ "]" r"[" ] ~ +"#                 => "][");
+make_test!(chars         : r"'a' ' ' '\'' '\n' '\u{e9}' '[' ']'" => @['a', ' ', '\'', '\n', 'é', '[', ']']);
+make_test!(char_in_block : "{ '}' } ~"                           => '}');
+make_test!(hex           : "0xFF -0x1f"                          => @[255, -31]);
+make_test!(oct_bin       : "0o17 0b1010"                         => @[15, 10]);
+make_test!(separators    : "1_000_000 0b1111_0000 1_0.5"         => @[1_000_000, 0b1111_0000, 10.5]);
+make_test!(scientific    : "1e3 2.5E-1 -1e2"                     => @[1e3, 2.5e-1, -1e2]);
+make_test!(line_comment  : "1 -- 2 +\n3 +"                       => 4);
+make_test!(block_comment : "1 (* 2 (* nested *) + *) 3 +"        => 4);
+make_test!(comment_block : "[ 1 2 ] { -- }\n ) } %"              => [2, 3]);
+
+#[test]
+fn invalid_literals() {
+    for (program, error) in [
+        ("0xFG", "invalid number literal `0xFG`"),
+        ("0b102", "invalid number literal `0b102`"),
+        ("0x", "invalid number literal `0x`"),
+        ("1.2.3", "invalid number literal `1.2.3`"),
+        ("'ab'", "invalid char literal `'ab'`"),
+        ("''", "invalid char literal `''`"),
+        (r"'\q'", r"invalid escape sequence `\q`"),
+        ("'a", "unclosed `'`"),
+    ] {
+        assert_eq!(
+            stack_machine::run(program).unwrap_err().to_string(),
+            format!("syntax error at 1:1: {}", error)
+        );
+    }
+}
+
+#[test]
+fn invalid_escapes() {
+    for (program, escape) in [
+        (r#""\q""#, r"\q"),
+        (r#""\u{110000}""#, r"\u{110000}"),
+        (r#""\u""#, r"\u"),
+    ] {
+        assert_eq!(
+            stack_machine::run(program).unwrap_err().to_string(),
+            format!("syntax error at 1:1: invalid escape sequence `{}`", escape)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{make_error_test, make_test};
+use stack_machine::{Segmentation, Stack, Value};
+use std::io;
+
+make_test!(char_add               : "'a' 1 + 'é' 1 + 1 'a' +"                                          => @['b', 'ê', 'b']);
+make_test!(char_add_chars         : "'\\0' 'é' +"                                                      => 'é');
+make_test!(char_sub               : "'ê' 1 - 'c' 'a' -"                                                => @['é', 2]);
+make_test!(char_conversions       : "'é' i 'é' f 233 c 233.0 c"                                        => @[233, 233.0, 'é', 'é']);
+make_test!(length                 : "\"ação\" ,"                                                       => 4);
+make_test!(index                  : "\"ação\" 1 ="                                                     => 'ç');
+make_test!(take                   : "\"ação\" 2 < \"ação\" 2 >"                                        => @["aç", "ão"]);
+make_test!(take_all               : "\"ação\" 4 < \"ação\" 4 > \"ação\" 0 <"                           => @["ação", "ação", ""]);
+make_test!(uncons                 : "\"ção\" ( \"ção\" )"                                              => @["ão", 'ç', "çã", 'o']);
+make_test!(combining              : "\"e\\u{301}\" ,"                                                  => 2);
+make_test!(find                   : "\"aéb\" \"b\" # \"aéb\" 'b' # \"ação\" \"ão\" # \"ação\" \"x\" #" => @[2, 2, 2, -1]);
+make_test!(find_agrees_with_index : "\"aéb\" _ 'b' # ="                                                => 'b');
+make_test!(filter                 : "\"éab\" { 'a' = } , \"ação\" { 'a' = ! } ,"                       => @["a", "ção"]);
+make_test!(map                    : "\"ação\" { 1 + } %"                                               => "bèäp");
+
+make_error_test!(add_below_zero      : "'a' -100 +"      => "runtime error at 1:10: cannot apply `add` to char 'a' and integer -100");
+make_error_test!(add_into_surrogates : "'\\u{d7ff}' 1 +" => "runtime error at 1:14: cannot apply `add` to char '\\u{d7ff}' and integer 1");
+make_error_test!(float_to_surrogate  : "55296.0 c"       => "runtime error at 1:9: cannot convert float 55296.0 to char");
+make_error_test!(take_past_end       : "\"ação\" 5 <"    => "runtime error at 1:10: cannot apply `str_end` to string \"ação\" and integer 5");
+
+fn run_graphemes(s: &str) -> Vec<Value> {
+    let mut input = io::empty();
+    let mut stack = Stack::with_input(&mut input);
+    stack.set_segmentation(Segmentation::Graphemes);
+    stack_machine::run_on(s, stack).unwrap()
+}
+
+#[test]
+fn graphemes() {
+    let accented = "\"a\\u{327}a\\u{303}o\"";
+    assert_eq!(
+        run_graphemes(&format!("{0} , {0} 1 = {0} 2 <", accented)),
+        vec![
+            Value::from(3),
+            Value::from("a\u{303}"),
+            Value::from("a\u{327}a\u{303}")
+        ]
+    );
+    assert_eq!(
+        run_graphemes(&format!("{} ) ; ( \"e\\u{{301}}\" 1 >", accented)),
+        vec![
+            Value::from("a\u{303}"),
+            Value::from("a\u{327}"),
+            Value::from("e\u{301}")
+        ]
+    );
+    assert_eq!(
+        run_graphemes("[ \"ab\" \"e\\u{301}\" ] { , } %"),
+        vec![Value::from([2, 1])]
+    );
+    assert_eq!(
+        run_graphemes("\"e\\u{301}b\" \"b\" # \"e\\u{301}b\" \"e\" #"),
+        vec![Value::from(1), Value::from(-1)]
+    );
+    assert_eq!(
+        run_graphemes("\"e\\u{301}xa\\u{303}\" { 'x' = ! } , \"e\\u{301}x\" { } %"),
+        vec![Value::from("e\u{301}a\u{303}"), Value::from("e\u{301}x")]
+    );
+}
+
+#[test]
+fn grapheme_sets() {
+    assert_eq!(
+        run_graphemes("\"e\u{301}e\" u"),
+        vec![Value::from("e\u{301}e")]
+    );
+    assert_eq!(
+        run_graphemes(
+            "\"e\u{301}e\" \"e\" - \"e\u{301}x\" \"e\" & \"e\" \"e\u{301}\" | \"e\u{301}x\" \"xe\" ^"
+        ),
+        vec![
+            Value::from("e\u{301}"),
+            Value::from(""),
+            Value::from("ee\u{301}"),
+            Value::from("e\u{301}e")
+        ]
+    );
+}
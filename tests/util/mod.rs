@@ -70,9 +70,37 @@ macro_rules! make_test {
     };
 }
 
+#[macro_export]
+macro_rules! make_error_test {
+    ($name:ident: $input:expr => $msg:expr) => {
+        #[test]
+        fn $name() {
+            let msg = ::std::format!("==> Test input was: '{}' => {:?}", $input, $msg);
+            $crate::assert_that!(
+                ::stack_machine::run($input).expect_err(&msg).to_string(),
+                $msg,
+                "{}",
+                msg
+            )
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! v {
     ($v:expr) => {
         ::stack_machine::Value::from($v)
     };
 }
+
+/// Runs `f` on a thread with a native stack big enough for programs that nest as deeply as the
+/// default limit allows, which the test threads' stacks aren't in a debug build.
+pub fn on_big_stack(f: impl FnOnce() + Send + 'static) {
+    let thread = std::thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(f)
+        .expect("failed to start the test's thread");
+    if let Err(panic) = thread.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{make_error_test, make_test};
+use stack_machine::{Mode, Repl, Value};
+use std::io;
+
+make_test!(predefined                  : "A B C D E F N S X Y Z"                             => @[10, 11, 12, 13, 14, 15, '\n', ' ', 0, 1, 2]);
+make_test!(other_letters_start_at_zero : "G Q"                                               => @[0, 0]);
+make_test!(assign_letter               : "5 :A ; A A +"                                      => 10);
+make_test!(long_names                  : "3 :count ; 4 :total_2 ; count total_2 *"           => 12);
+make_test!(unicode_names               : "7 :ação ; ação"                                    => 7);
+make_test!(assign_keeps_top            : "1 :one"                                            => 1);
+make_test!(read_outer_in_block         : "2 :k ; [ 1 2 3 ] { k * } %"                        => @[[2, 4, 6]]);
+make_test!(block_writes_are_local      : "1 :A ; [ 1 2 ] { :A ; A } % A"                     => @[[1, 2], 1]);
+make_test!(block_shadows_outer         : "2 :k ; [ 1 2 ] { :k } % ; k"                       => 2);
+make_test!(sort_block_is_local         : "1 :A ; [ 3 1 2 ] { :A } $ ; A"                     => 1);
+make_test!(block_updates_outer         : "5 :A ; { A 1 + :A ; } ~ A"                         => 6);
+make_test!(block_new_is_local          : "{ 7 :fresh ; fresh } ~ B"                          => @[7, 11]);
+make_test!(global_write                : "0 :n ; [ 1 2 3 ] { ::n } % ; n"                    => 3);
+make_test!(global_write_in_loop        : "0 :j ; { j ) ::j 5 < } w j"                        => 5);
+make_test!(loop_updates_outer          : "0 :k ; { k 1 + :k ; k 5 < } w k"                   => 5);
+make_test!(scopes_nest                 : "1 :v ; { 2 :v ; { v } ~ v } ~ v"                   => @[2, 2, 2]);
+make_test!(inner_updates_enclosing     : "{ 2 :m ; { m 1 + :m ; } ~ m } ~"                   => 3);
+make_test!(word_locals                 : ": sq { :x x * } 1 :x ; 5 sq x"                     => @[25, 1]);
+make_test!(word_loop_locals            : ": count { 0 :n ; { n 1 + :n ; n 3 < } w n } count" => 3);
+make_test!(word_letters_are_local      : ": set { 4 :Q ; } set Q"                            => 0);
+make_test!(word_sets_global            : ": set { 4 ::Q ; } set Q"                           => 4);
+make_test!(recursive_locals            : ": fact { :n 1 > { n n ( fact * } { 1 } ? } 6 fact" => 720);
+make_test!(if_branch_scope             : "1 { 3 :tmp ; tmp } { 0 } ? A"                      => @[3, 10]);
+
+make_error_test!(redefine_builtin      : "1 :p"                                     => "syntax error at 1:3: cannot redefine built-in `p`");
+make_error_test!(redefine_builtin_word : "1 :mk"                                    => "syntax error at 1:3: cannot redefine built-in `mk`");
+make_error_test!(invalid_name          : "1 :2x"                                    => "syntax error at 1:3: unknown token `:2x`");
+make_error_test!(unknown_name          : "count"                                    => "syntax error at 1:1: unknown token `count`");
+make_error_test!(block_local_gone      : "{ 1 :inner } ~ inner"                     => "runtime error at 1:16: `inner` is read before it is assigned");
+make_error_test!(word_hides_caller     : ": peek { secret } { 1 :secret ; peek } ~" => "runtime error at 1:10: `secret` is read before it is assigned");
+make_error_test!(read_before_assign    : "late 1 :late"                             => "runtime error at 1:1: `late` is read before it is assigned");
+
+#[test]
+fn golf() {
+    assert_eq!(
+        stack_machine::run_with_mode("5:x;x x*{3:x;x}~x", Mode::Golf).unwrap(),
+        vec![Value::from(25), Value::from(3), Value::from(3)]
+    );
+    assert_eq!(
+        stack_machine::run_with_mode("5:Ap", Mode::Golf).unwrap(),
+        vec![Value::from(5)]
+    );
+}
+
+#[test]
+fn repl_remembers_variables() {
+    let mut input = io::empty();
+    let mut repl = Repl::new(&mut input);
+    repl.next_line("6 :size ;");
+    repl.next_line("size size *");
+    assert_eq!(repl.into_vec(), vec![Value::from(36)]);
+}
@@ -0,0 +1,77 @@
+use crate::{make_error_test, make_test, util::on_big_stack};
+use stack_machine::{Repl, Stack, Value};
+use std::io;
+
+make_test!(define_and_call           : ": sq { _ * } 5 sq 3 sq"                                                         => @[25, 9]);
+make_test!(definition_pushes_nothing : "1 : two { 2 }"                                                                  => 1);
+make_test!(empty_body                : ": nop { } 4 nop"                                                                => 4);
+make_test!(recursion                 : ": fact { _ 1 > { _ ( fact * } { ; 1 } ? } 10 fact"                              => 3628800);
+make_test!(mutual_recursion          : ": even { _ { ( odd } { ; 1 } ? } : odd { _ { ( even } { ; 0 } ? } 7 even 7 odd" => @[0, 1]);
+make_test!(redefinition              : ": x { 1 } x : x { 2 } x"                                                        => @[1, 2]);
+make_test!(late_binding              : ": a { b b + } : b { 1 } a : b { 10 } a"                                         => @[2, 20]);
+make_test!(nested_definition         : ": outer { : inner { 7 } 1 } outer inner"                                        => @[1, 7]);
+make_test!(defined_in_block          : "{ : three { 3 } } ~ three"                                                      => 3);
+make_test!(used_in_block             : ": inc { ) } [ 1 2 3 ] { inc } %"                                                => @[[2, 3, 4]]);
+make_test!(pop_in_body               : ": sq { :x ; x x * } 5 sq"                                                       => 25);
+make_test!(drop_two                  : ": drop2 { ; ; } 1 2 3 drop2"                                                    => 1);
+make_test!(blocks_in_body            : ": pick { { 1 } { 2 } ? } 0 pick"                                                => 2);
+
+make_error_test!(redefine_operator       : ": + { 1 }"         => "syntax error at 1:3: cannot redefine built-in `+`");
+make_error_test!(redefine_letter         : ": A { 1 }"         => "syntax error at 1:3: cannot redefine built-in `A`");
+make_error_test!(number_name             : ": 12 { 1 }"        => "syntax error at 1:3: `12` is not a valid word name");
+make_error_test!(malformed_hex_name      : ": 0xZZ { 1 }"      => "syntax error at 1:3: `0xZZ` is not a valid word name");
+make_error_test!(malformed_rational_name : ": 1/0 { 1 }"       => "syntax error at 1:3: `1/0` is not a valid word name");
+make_error_test!(block_name              : ": { x } { 1 }"     => "syntax error at 1:3: `{ x }` is not a valid word name");
+make_error_test!(missing_name            : "1 :"               => "syntax error at 1:3: unclosed `:`");
+make_error_test!(missing_body            : "1 : one"           => "syntax error at 1:5: expected a block with the body of `one`");
+make_error_test!(body_not_block          : ": sq _ * ; 5 sq"   => "syntax error at 1:6: expected a block with the body of `sq`");
+make_error_test!(unknown_word            : "bogus"             => "syntax error at 1:1: unknown token `bogus`");
+make_error_test!(call_before_definition  : "late : late { 1 }" => "runtime error at 1:1: `late` is called before it is defined");
+
+#[test]
+fn deep_recursion() {
+    on_big_stack(|| {
+        assert_eq!(
+            stack_machine::run(": sum { _ { _ ( sum + } { } ? } 1000 sum").unwrap(),
+            vec![Value::from(500500)]
+        );
+        assert_eq!(
+            stack_machine::run(": down { _ { ( down } { } ? } 5000 down")
+                .unwrap_err()
+                .to_string(),
+            "runtime error at 1:16: blocks and words nested deeper than the maximum of 2500"
+        );
+    });
+}
+
+#[test]
+fn recursion_limit_resets() {
+    on_big_stack(|| {
+        assert_eq!(
+            stack_machine::run(": down { _ { ( down } { } ? } 1200 down 1200 down").unwrap(),
+            vec![Value::from(0), Value::from(0)]
+        );
+    });
+}
+
+#[test]
+fn recursion_limit_is_configurable() {
+    let mut input = io::empty();
+    let mut stack = Stack::with_input(&mut input);
+    stack.set_max_call_depth(10);
+    assert_eq!(
+        stack_machine::run_on(": down { _ { ( down } { } ? } 20 down", stack)
+            .unwrap_err()
+            .to_string(),
+        "runtime error at 1:16: blocks and words nested deeper than the maximum of 10"
+    );
+}
+
+#[test]
+fn repl_remembers_words() {
+    let mut input = io::empty();
+    let mut repl = Repl::new(&mut input);
+    repl.next_line(": sq { _ * }");
+    repl.next_line("6 sq");
+    assert_eq!(repl.into_vec(), vec![Value::from(36)]);
+}